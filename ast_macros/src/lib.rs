@@ -10,8 +10,8 @@ enum TokenType {
     Punctuation(String),
     Branch(String, String), // Branch name and case name
     EndBranch(String),
-    Field(Ident, syn::Type), // Field name and type
-    Optional,                // Field name and type
+    Field(Ident, Box<syn::Type>),            // Field name and type
+    Optional,                                // Field name and type
     Repeating(Option<String>, usize, usize), // Min, max, field name and type
     ScopeStart(String),
     ScopeEnd,
@@ -164,7 +164,7 @@ pub fn ast_node(_attr: TokenStream, code: TokenStream) -> TokenStream {
         {
             continue; // Skip unit fields
         }
-        tokens.push(TokenType::Field(field_name, field_type));
+        tokens.push(TokenType::Field(field_name, Box::new(field_type)));
     }
 
     generate_from_tokens(tokens, input.ident.clone())
@@ -193,10 +193,7 @@ fn generate_from_tokens(mut tokens: Vec<TokenType>, struct_type: syn::Ident) ->
             }
             TokenType::Keyword(keyword) => {
                 matchers.push(quote::quote! {
-                    let tokenizer::Token::IdentifierOrKeyword(ident_or_keyword) = data.next()? else {
-                        return None;
-                    };
-                    if ident_or_keyword.parsed() != #keyword {
+                    if data.next()?.kind.keyword().is_none_or(|keyword| keyword.as_str() != #keyword) {
                         return None;
                     }
                 });
@@ -204,7 +201,7 @@ fn generate_from_tokens(mut tokens: Vec<TokenType>, struct_type: syn::Ident) ->
             TokenType::Punctuation(punct) => {
                 let punct_ident = syn::Ident::new(punct, Span::call_site().into());
                 matchers.push(quote::quote! {
-                    let tokenizer::Token::Punctuation(tokenizer::punctuation::Punctuation::#punct_ident) = data.next()? else {
+                    let tokenizer::TokenKind::Punctuation(tokenizer::punctuation::Punctuation::#punct_ident) = data.next()?.kind else {
                         return None;
                     };
                 });
@@ -256,14 +253,24 @@ fn generate_from_tokens(mut tokens: Vec<TokenType>, struct_type: syn::Ident) ->
     let struct_header: TokenStream = quote::quote! {
         #[derive(Debug)]
         struct #struct_type {
-            #(#fields),*
+            #(#fields,)*
+            span: tokenizer::span::Span,
+        }
+
+        impl #struct_type {
+            pub fn span(&self) -> tokenizer::span::Span {
+                self.span
+            }
         }
 
         impl ast_trait::AstNode for #struct_type {
             fn parse_node(mut data: ast_trait::TokenIterator) -> Option<(Self, ast_trait::TokenIterator)> {
+                let start_data = data.clone();
                 #(#matchers)*
+                let span = ast_trait::consumed_span(&start_data, &data);
                 Some((Self {
-                    #(#names),*
+                    #(#names,)*
+                    span,
                 }, data))
             }
         }
@@ -276,10 +283,7 @@ fn generate_from_tokens(mut tokens: Vec<TokenType>, struct_type: syn::Ident) ->
 
 fn generate_sopes(tokens: &mut Vec<TokenType>) -> TokenStream {
     let mut result = TokenStream::new();
-    loop {
-        let Some(end) = tokens.iter().position(|a| matches!(a, TokenType::ScopeEnd)) else {
-            break;
-        };
+    while let Some(end) = tokens.iter().position(|a| matches!(a, TokenType::ScopeEnd)) {
         let Some(start) = tokens[..end]
             .iter()
             .rposition(|a| matches!(a, TokenType::ScopeStart(_)))
@@ -297,7 +301,7 @@ fn generate_sopes(tokens: &mut Vec<TokenType>) -> TokenStream {
 
         tokens.splice(
             start..=end,
-            [TokenType::Field(combined_name.clone(), ty.clone())],
+            [TokenType::Field(combined_name.clone(), Box::new(ty.clone()))],
         );
     }
 
@@ -349,7 +353,7 @@ fn generate_branches(tokens: &mut Vec<TokenType>) -> TokenStream {
 
         tokens.splice(
             real_start..=og_end,
-            [TokenType::Field(combined_name.clone(), ty.clone())],
+            [TokenType::Field(combined_name.clone(), Box::new(ty.clone()))],
         );
     }
     result
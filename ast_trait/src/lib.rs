@@ -2,11 +2,28 @@ use core::marker::Sized;
 use core::option::Option;
 use std::fmt::Debug;
 
-use tokenizer::Token;
 use tokenizer::punctuation::Punctuation;
+use tokenizer::span::Span;
+use tokenizer::{Token, TokenKind};
 
 pub type TokenIterator<'a> = std::iter::Peekable<core::slice::Iter<'a, tokenizer::Token>>;
 
+/// Span of the tokens consumed between `start` and `end`, where `end` was advanced from `start`.
+/// Nodes that consumed nothing get an empty span at the next token.
+pub fn consumed_span(start: &TokenIterator, end: &TokenIterator) -> Span {
+    let consumed = start.len() - end.len();
+    let mut tokens = start.clone();
+    let Some(first) = tokens.peek() else {
+        return Span::default();
+    };
+    if consumed == 0 {
+        return first.span.shrink_to_start();
+    }
+    let first = first.span;
+    let last = tokens.nth(consumed - 1).unwrap().span;
+    first.to(last)
+}
+
 pub trait AstNode: Sized {
     fn parse_node(data: TokenIterator) -> Option<(Self, TokenIterator)>;
     fn parse_repeating(
@@ -29,7 +46,10 @@ pub trait AstNode: Sized {
 
         loop {
             if let Some(sep) = &separator {
-                if let Some(Token::Punctuation(other_sep)) = data_iterator.peek()
+                if let Some(Token {
+                    kind: TokenKind::Punctuation(other_sep),
+                    ..
+                }) = data_iterator.peek()
                     && other_sep == sep
                 {
                 } else {
@@ -6,7 +6,7 @@ mod name;
 use std::{collections::HashMap, path::Path};
 
 use ast_trait::TokenIterator;
-use tokenizer::tokenize_file;
+use tokenizer::{Edition, TokenizeOptions, lex_error::TokenizeFileError, tokenize_file};
 
 pub struct Module {
     inner_attributes: (),
@@ -17,17 +17,26 @@ pub struct Crate {
     modules: HashMap<Box<[Box<str>]>, Module>,
 }
 
-pub fn parse_crate(root_path: &Path, root_file_name: &str) -> Crate {
+pub fn parse_crate(root_path: &Path, root_file_name: &str, edition: Edition) -> Crate {
     let mut modules = HashMap::new();
 
     let mut files_to_parse = Vec::new();
     files_to_parse.push(Path::new(root_file_name));
 
+    let options = TokenizeOptions {
+        edition,
+        ..Default::default()
+    };
     while let Some(file) = files_to_parse.pop() {
         let mut full_file_path = root_path.to_owned();
         full_file_path.push(file);
 
-        let tokens = tokenize_file(&full_file_path);
+        let tokens = match tokenize_file(&full_file_path, &options) {
+            Ok(tokens) => tokens,
+            // keep going on the recovered stream, error tokens just won't parse
+            Err(TokenizeFileError::Lex(err)) => err.tokens,
+            Err(err) => panic!("Failed to tokenize {}: {}", full_file_path.display(), err),
+        };
         let parsed = parse_token_stream(tokens.iter().peekable());
         // parse tokens to find mod declarations and add them to files_to_parse
         let k = file
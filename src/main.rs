@@ -1,31 +1,394 @@
-use tokenizer::tokenize_file;
-
-fn tokenize_dir_recursively(path: &std::path::Path) -> Vec<(std::path::PathBuf, Box<[tokenizer::Token]>)> {
-    let mut result = Vec::new();
-    if path.is_dir() {
-        for entry in std::fs::read_dir(path).unwrap() {
-            let entry = entry.unwrap();
-            let path = entry.path();
-            result.extend(tokenize_dir_recursively(&path));
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Mutex, mpsc},
+    time::Instant,
+};
+
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use tokenizer::{SrcIterator, TokenizeOptions, dump, lex_error::LexError, span::FileId, tokenize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DumpFormat {
+    Text,
+    JsonLines,
+    Binary,
+}
+
+impl DumpFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "tokens.txt",
+            Self::JsonLines => "tokens.jsonl",
+            Self::Binary => "tokens.bin",
         }
-    } else {
-        //skip if not .rs
-        if path.extension().and_then(|s| s.to_str()) != Some("rs") {
-            return result;
+    }
+}
+
+struct Options {
+    tokenize: TokenizeOptions,
+    dump: Option<DumpFormat>,
+}
+
+struct TokenizedFile {
+    path: PathBuf,
+    bytes: usize,
+    token_count: usize,
+}
+
+/// A file as a worker thread hands it over, before its dump is written
+struct Tokenized {
+    file: TokenizedFile,
+    /// The tokens in the requested dump format
+    dump: Vec<u8>,
+    failures: Vec<Failure>,
+}
+
+/// Why a file or directory couldn't be tokenized cleanly
+enum Failure {
+    Read(std::io::Error),
+    /// The file is still tokenized, with error tokens in place of the bad ones
+    Lex(Box<[LexError]>),
+    /// The file is tokenized, but its dump couldn't be written
+    Write(std::io::Error),
+}
+
+#[derive(Default)]
+struct Walk {
+    paths: Mutex<Vec<PathBuf>>,
+    failures: Mutex<Vec<(PathBuf, Failure)>>,
+}
+
+impl Walk {
+    fn fail(&self, path: &Path, failure: Failure) {
+        let mut failures = self.failures.lock().unwrap();
+        failures.push((path.to_path_buf(), failure));
+    }
+}
+
+/// Tokenizes every `.rs` file under `path` on all cores and hands each one to `on_file` along with
+/// its dump. Files come in order of their path and each one as soon as the ones before it are
+/// done, so the output doesn't depend on which thread got to a file first and only a few dumps
+/// are held in memory at a time. An error from `on_file` is a write failure of that file.
+fn tokenize_dir_recursively(
+    path: &Path,
+    options: &Options,
+    mut on_file: impl FnMut(&TokenizedFile, &[u8]) -> std::io::Result<()>,
+) -> (Vec<TokenizedFile>, Vec<(PathBuf, Failure)>) {
+    let walk = Walk::default();
+    rayon::scope(|scope| match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => walk_dir(scope, &walk, path.to_path_buf()),
+        Ok(_) if is_rust_file(path) => walk.paths.lock().unwrap().push(path.to_path_buf()),
+        Ok(_) => {}
+        Err(err) => walk.fail(path, Failure::Read(err)),
+    });
+    let mut paths = walk.paths.into_inner().unwrap();
+    let mut failures = walk.failures.into_inner().unwrap();
+    paths.sort();
+
+    let mut files = Vec::with_capacity(paths.len());
+    std::thread::scope(|threads| {
+        let (sender, receiver) = mpsc::channel();
+        threads.spawn(move || {
+            // bridged so that workers take the files in order instead of each one a range
+            paths.into_iter().enumerate().par_bridge().for_each_with(
+                sender,
+                |sender, (index, path)| {
+                    // only fails once the receiving end is gone, when nobody is waiting anymore
+                    let _ = sender.send((index, tokenize_file(path, options)));
+                },
+            );
+        });
+
+        // files that are done while one before them isn't
+        let mut waiting = BTreeMap::new();
+        let mut next = 0;
+        for (index, tokenized) in receiver {
+            waiting.insert(index, tokenized);
+            while let Some(tokenized) = waiting.remove(&next) {
+                next += 1;
+                let mut tokenized = match tokenized {
+                    Ok(tokenized) => tokenized,
+                    Err(failure) => {
+                        failures.push(failure);
+                        continue;
+                    }
+                };
+                if let Err(err) = on_file(&tokenized.file, &tokenized.dump) {
+                    tokenized.failures.push(Failure::Write(err));
+                }
+                for failure in tokenized.failures {
+                    failures.push((tokenized.file.path.clone(), failure));
+                }
+                files.push(tokenized.file);
+            }
+        }
+    });
+    // stable, so a file's failures stay in the order they happened
+    failures.sort_by(|a, b| a.0.cmp(&b.0));
+    (files, failures)
+}
+
+/// Every subdirectory is its own task, so idle threads steal whatever is left instead of waiting
+/// on a big directory
+fn walk_dir<'s>(scope: &rayon::Scope<'s>, walk: &'s Walk, path: PathBuf) {
+    let entries = match std::fs::read_dir(&path) {
+        Ok(entries) => entries,
+        Err(err) => return walk.fail(&path, Failure::Read(err)),
+    };
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                walk.fail(&path, Failure::Read(err));
+                continue;
+            }
+        };
+        let path = entry.path();
+        // symlinks aren't followed, so a link back up the tree can't loop forever
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => {
+                scope.spawn(move |scope| walk_dir(scope, walk, path))
+            }
+            Ok(file_type) if file_type.is_file() && is_rust_file(&path) => {
+                walk.paths.lock().unwrap().push(path)
+            }
+            Ok(_) => {}
+            Err(err) => walk.fail(&path, Failure::Read(err)),
+        }
+    }
+}
+
+fn is_rust_file(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("rs")
+}
+
+fn tokenize_file(path: PathBuf, options: &Options) -> Result<Tokenized, (PathBuf, Failure)> {
+    let src = match std::fs::read_to_string(&path) {
+        Ok(src) => src,
+        Err(err) => return Err((path, Failure::Read(err))),
+    };
+    let mut failures = Vec::new();
+    let iter = SrcIterator::new(&src);
+    let tokens = match tokenize(iter, FileId::register(&path), &options.tokenize) {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            failures.push(Failure::Lex(err.errors));
+            err.tokens
         }
-        //print path
-        println!("Tokenizing file: {}", path.display());
-    
-        let tokens = tokenize_file(path.to_str().unwrap());
-        result.push((path.to_path_buf(), tokens));
+    };
+    // rendered here so that dumping runs in parallel too, writing it out is left to the caller
+    let mut dump = Vec::new();
+    let written = match options.dump {
+        Some(DumpFormat::Text) => dump::write_text(&tokens, &src, &mut dump),
+        Some(DumpFormat::JsonLines) => dump::write_json_lines(&tokens, &src, &mut dump),
+        Some(DumpFormat::Binary) => dump::write_binary(&tokens, &src, &mut dump),
+        None => Ok(()),
+    };
+    if let Err(err) = written {
+        failures.push(Failure::Write(err));
     }
-    result
+    let file = TokenizedFile {
+        path,
+        bytes: src.len(),
+        token_count: tokens.len(),
+    };
+    Ok(Tokenized {
+        file,
+        dump,
+        failures,
+    })
+}
+
+/// Writes a file's dump, either to stdout or mirroring the tree under `out`
+fn write_dump(
+    root: &Path,
+    file: &TokenizedFile,
+    dump: &[u8],
+    format: DumpFormat,
+    out: Option<&Path>,
+) -> std::io::Result<()> {
+    let Some(out) = out else {
+        let mut stdout = std::io::stdout().lock();
+        // tells the files apart, the readers skip these lines
+        match format {
+            DumpFormat::Text => writeln!(stdout, "# {}", file.path.display())?,
+            DumpFormat::JsonLines => {
+                dump::write_json_file_line(&file.path.display().to_string(), &mut stdout)?
+            }
+            DumpFormat::Binary => unreachable!("binary dumps need an output directory"),
+        }
+        return stdout.write_all(dump);
+    };
+    let relative = match file.path.strip_prefix(root) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative,
+        _ => Path::new(file.path.file_name().unwrap()),
+    };
+    let mut path = out.join(relative).into_os_string();
+    path.push(".");
+    path.push(format.extension());
+    let path = PathBuf::from(path);
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    std::fs::write(path, dump)
+}
+
+fn usage() -> ! {
+    eprintln!("usage: src <path> [--dump text|json|binary] [--out <dir>]");
+    std::process::exit(2)
 }
 
 fn main() {
-    let start = std::time::Instant::now();
-    let dir = std::env::args().nth(1).expect("Please provide a directory path");
-    let _tokenized_files = tokenize_dir_recursively(std::path::Path::new(&dir));
-    let end = std::time::Instant::now();
-    println!("Finished tokenizing in {} seconds", (end - start).as_secs_f64());
+    let mut args = std::env::args().skip(1);
+    let mut dir = None;
+    let mut out = None;
+    let mut options = Options {
+        tokenize: TokenizeOptions::default(),
+        dump: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dump" => {
+                options.dump = match args.next().as_deref() {
+                    Some("text") => Some(DumpFormat::Text),
+                    Some("json") => Some(DumpFormat::JsonLines),
+                    Some("binary") => Some(DumpFormat::Binary),
+                    _ => usage(),
+                }
+            }
+            "--out" => out = Some(PathBuf::from(args.next().unwrap_or_else(|| usage()))),
+            _ if dir.is_none() => dir = Some(PathBuf::from(arg)),
+            _ => usage(),
+        }
+    }
+    let Some(dir) = dir else { usage() };
+    if options.dump == Some(DumpFormat::Binary) && out.is_none() {
+        eprintln!("binary dumps need an output directory, pass --out");
+        usage();
+    }
+
+    let start = Instant::now();
+    let (tokenized_files, failures) =
+        tokenize_dir_recursively(&dir, &options, |file, dump| match options.dump {
+            Some(format) => write_dump(&dir, file, dump, format, out.as_deref()),
+            None => Ok(()),
+        });
+    let elapsed = start.elapsed().as_secs_f64();
+
+    // stdout may be taken by the dumps
+    let summary = if options.dump.is_some() && out.is_none() {
+        print_summary(&mut std::io::stderr(), &tokenized_files, &failures, elapsed)
+    } else {
+        print_summary(&mut std::io::stdout(), &tokenized_files, &failures, elapsed)
+    };
+    summary.expect("failed to print the summary");
+}
+
+fn print_summary(
+    out: &mut impl Write,
+    tokenized_files: &[TokenizedFile],
+    failures: &[(PathBuf, Failure)],
+    elapsed: f64,
+) -> std::io::Result<()> {
+    if !failures.is_empty() {
+        writeln!(out, "{} files or directories failed:", failures.len())?;
+        for (path, failure) in failures {
+            match failure {
+                Failure::Read(err) => {
+                    writeln!(out, "error: failed to read {}: {}", path.display(), err)?
+                }
+                Failure::Write(err) => writeln!(
+                    out,
+                    "error: failed to write the token dump of {}: {}",
+                    path.display(),
+                    err
+                )?,
+                Failure::Lex(errors) => {
+                    for error in errors {
+                        writeln!(out, "error: {}", error)?;
+                    }
+                }
+            }
+        }
+    }
+
+    let files = tokenized_files.len();
+    let bytes: usize = tokenized_files.iter().map(|file| file.bytes).sum();
+    let tokens: usize = tokenized_files.iter().map(|file| file.token_count).sum();
+    let megabytes = bytes as f64 / 1_000_000.0;
+    writeln!(
+        out,
+        "Tokenized {} files ({:.2} MB, {} tokens) in {:.3} seconds",
+        files, megabytes, tokens, elapsed
+    )?;
+    writeln!(
+        out,
+        "{:.0} files/s, {:.2} MB/s, {:.0} tokens/s",
+        files as f64 / elapsed,
+        megabytes / elapsed,
+        tokens as f64 / elapsed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_are_collected() {
+        let root = std::env::temp_dir().join(format!("src-failures-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        std::fs::write(root.join("a.rs"), "fn main() {}").unwrap();
+        std::fs::write(root.join("b/c.rs"), "let x = § + 1;").unwrap();
+        // not UTF-8, so it can't be read as source
+        std::fs::write(root.join("d.rs"), b"fn \xff() {}").unwrap();
+        std::fs::write(root.join("e.txt"), "§").unwrap();
+
+        let options = Options {
+            tokenize: TokenizeOptions::default(),
+            dump: Some(DumpFormat::Text),
+        };
+        let mut written = Vec::new();
+        let (files, failures) = tokenize_dir_recursively(&root, &options, |file, dump| {
+            written.push(file.path.strip_prefix(&root).unwrap().to_path_buf());
+            assert!(!dump.is_empty());
+            match file.path.ends_with("a.rs") {
+                true => Err(std::io::Error::other("disk full")),
+                false => Ok(()),
+            }
+        });
+        assert_eq!(written, [Path::new("a.rs"), Path::new("b/c.rs")]);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].token_count, 6);
+
+        let mut summary = Vec::new();
+        print_summary(&mut summary, &files, &failures, 1.0).unwrap();
+        let summary = String::from_utf8(summary).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let lines: Vec<_> = summary.lines().collect();
+        assert_eq!(lines.len(), 6, "{summary}");
+        assert_eq!(lines[0], "3 files or directories failed:");
+        assert_eq!(
+            lines[1],
+            format!(
+                "error: failed to write the token dump of {}: disk full",
+                root.join("a.rs").display()
+            )
+        );
+        assert!(lines[2].starts_with("error: "), "{}", lines[2]);
+        assert!(lines[2].contains("c.rs:1:9"), "{}", lines[2]);
+        assert!(
+            lines[3].starts_with(&format!(
+                "error: failed to read {}: ",
+                root.join("d.rs").display()
+            )),
+            "{}",
+            lines[3]
+        );
+        assert!(
+            lines[4].starts_with("Tokenized 2 files (0.00 MB, "),
+            "{}",
+            lines[4]
+        );
+    }
 }
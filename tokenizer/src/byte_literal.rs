@@ -7,22 +7,37 @@ use crate::suffix::Suffix;
 #[derive(Debug)]
 pub struct ByteLiteral {
     value: u8,
+    source: Box<str>,
     suffix: Option<Suffix>
 }
 
+impl ByteLiteral {
+    /// The byte with escapes decoded
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+
+    /// The literal as written, `b` and quotes included and suffix excluded
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+}
+
 impl tokenizer_trait::Token for ByteLiteral {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let start = data.offset();
         if data.next()? != 'b' || data.next()? != '\'' {
             return None;
         }
 
         let byte;
-        if *data.peek()? == '\\' {
+        if data.peek()? == '\\' {
             //byte escape
-            if let Some(byte_escape) = crate::string_escapes::ByteEscape::parse_token(data.clone()) {
-                if byte_escape.0.value() == 0 {
-                    return None;
-                }
+            if let Some(byte_escape) = crate::string_escapes::ByteEscape::parse_token(data) {
                 byte = byte_escape.0.value();
                 data = byte_escape.1;
             } else {
@@ -42,14 +57,15 @@ impl tokenizer_trait::Token for ByteLiteral {
         if data.next()? != '\'' {
             return None;
         }
-        if let Some(suffix) = Suffix::parse_token(data.clone()) {
+        let source = data.slice_from(start).into();
+        if let Some(suffix) = Suffix::parse_token(data) {
             return Some((
-                Self { value: byte, suffix: Some(suffix.0) },
+                Self { value: byte, source, suffix: Some(suffix.0) },
                 suffix.1,
             ));
         }
         Some((
-            Self { value: byte, suffix: None },
+            Self { value: byte, source, suffix: None },
             data,
         ))
     }
@@ -5,24 +5,49 @@ use crate::{string_escapes::ByteEscape, suffix::Suffix};
 
 #[derive(Debug)]
 pub struct ByteStringLiteral {
-    value: Vec<u8>,
+    /// Only there when the literal has escapes, otherwise the value is the source minus `b` and the
+    /// quotes
+    decoded: Option<Box<[u8]>>,
+    source: Box<str>,
     suffix: Option<Suffix>
 }
 
+impl ByteStringLiteral {
+    /// The bytes with escapes decoded
+    pub fn value(&self) -> &[u8] {
+        match &self.decoded {
+            Some(decoded) => decoded,
+            None => self.source[2..self.source.len() - 1].as_bytes(),
+        }
+    }
+
+    /// The literal as written, `b` and quotes included and suffix excluded
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+}
+
 impl tokenizer_trait::Token for ByteStringLiteral {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let start = data.offset();
         if data.next()? != 'b' || data.next()? != '"' {
             return None;
         }
-    
-        let mut content = Vec::new();
+
+        // only copied out of the source once there is an escape to decode
+        let content_start = data.offset();
+        let mut decoded: Option<Vec<u8>> = None;
+
         while let Some(chr) = data.peek() {
-            if *chr == '\\' {
+            if chr == '\\' {
+                let content = decoded
+                    .get_or_insert_with(|| data.slice_from(content_start).as_bytes().to_vec());
                 //handle escapes
-                if let Some(byte_escape) = ByteEscape::parse_token(data.clone()) {
-                    if byte_escape.0.value() == 0 {
-                        return None;
-                    }
+                if let Some(byte_escape) = ByteEscape::parse_token(data) {
                     content.push(byte_escape.0.value());
                     data = byte_escape.1;
                     continue;
@@ -31,7 +56,7 @@ impl tokenizer_trait::Token for ByteStringLiteral {
                 data.next()?;
                 if data.next()? == '\n' { // \LF string continue
                     while let Some(chr) = data.peek() {
-                        if *chr == ' ' || *chr == '\t' || *chr == '\n' || *chr == '\r' {
+                        if chr == ' ' || chr == '\t' || chr == '\n' || chr == '\r' {
                             data.next();
                         } else {
                             break;
@@ -39,28 +64,34 @@ impl tokenizer_trait::Token for ByteStringLiteral {
                     }
                     continue;
                 }
+
+                return None;
             }
             let chr = data.next()?;
             if chr == '\r' {
                 return None;
             }
             if chr == '"' {
-                let temp_peekable = data.clone();
-                if let Some(suffix) = Suffix::parse_token(temp_peekable.clone()) {
+                let decoded = decoded.map(Vec::into_boxed_slice);
+                let temp_peekable = data;
+                let source = data.slice_from(start).into();
+                if let Some(suffix) = Suffix::parse_token(temp_peekable) {
                     return Some((
-                        Self { value: content, suffix: Some(suffix.0) },
+                        Self { decoded, source, suffix: Some(suffix.0) },
                         suffix.1,
                     ));
                 }
                 return Some((
-                    Self { value: content, suffix: None },
+                    Self { decoded, source, suffix: None },
                     temp_peekable,
                 ));
             }
             if !chr.is_ascii() {
                 return None;
             }
-            content.push(chr as u8);
+            if let Some(decoded) = &mut decoded {
+                decoded.push(chr as u8);
+            }
         }
 
         None
@@ -1,16 +1,46 @@
-use tokenizer_trait::SrcIterator;
+use tokenizer_trait::{Edition, SrcIterator};
+
+use std::ffi::{CStr, CString};
 
 use crate::{string_escapes::{ByteEscape, UnicodeEscape}, suffix::Suffix};
 
 
 #[derive(Debug)]
 pub struct CStringLiteral {
-    value: Vec<u8>,
+    value: CString,
+    source: Box<str>,
     suffix: Option<Suffix>
 }
 
+impl CStringLiteral {
+    /// The string with escapes decoded. `\x` escapes above 0x7f are raw bytes and `\u` escapes
+    /// are UTF-8, so it doesn't have to be valid UTF-8.
+    pub fn value(&self) -> &CStr {
+        &self.value
+    }
+
+    /// The decoded bytes including the trailing NUL, as they would be laid out in memory
+    pub fn bytes_with_nul(&self) -> &[u8] {
+        self.value.to_bytes_with_nul()
+    }
+
+    /// The literal as written, `c` and quotes included and suffix excluded
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+}
+
 impl tokenizer_trait::Token for CStringLiteral {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        // C strings exist since 2021
+        if data.edition() < Edition::Edition2021 {
+            return None;
+        }
+        let start = data.offset();
         if data.next()? != 'c' || data.next()? != '"' {
             return None;
         }
@@ -18,9 +48,10 @@ impl tokenizer_trait::Token for CStringLiteral {
         let mut content = Vec::new();
 
         while let Some(chr) = data.peek() {
-            if *chr == '\\' {
+            if chr == '\\' {
                 //handle escapes
-                if let Some(byte_escape) = ByteEscape::parse_token(data.clone()) {
+                // NUL can't be in a C string in any form, it would end the string early
+                if let Some(byte_escape) = ByteEscape::parse_token(data) {
                     if byte_escape.0.value() == 0 {
                         return None;
                     }
@@ -28,19 +59,20 @@ impl tokenizer_trait::Token for CStringLiteral {
                     data = byte_escape.1;
                     continue;
                 }
-                if let Some(unicode_escape) = UnicodeEscape::parse_token(data.clone()) {
-                    if unicode_escape.0.is_null() {
+                if let Some(unicode_escape) = UnicodeEscape::parse_token(data) {
+                    let chr = unicode_escape.0.to_char()?;
+                    if chr == '\0' {
                         return None;
                     }
                     data = unicode_escape.1;
-                    content.extend_from_slice(&unicode_escape.0.to_utf8_bytes());
+                    content.extend_from_slice(chr.encode_utf8(&mut [0; 4]).as_bytes());
 
                     continue;
                 }
                 data.next()?;
                 if data.next()? == '\n' { // \LF string continue
                     while let Some(chr) = data.peek() {
-                        if *chr == ' ' || *chr == '\t' || *chr == '\n' || *chr == '\r' {
+                        if chr == ' ' || chr == '\t' || chr == '\n' || chr == '\r' {
                             data.next();
                         } else {
                             break;
@@ -57,19 +89,83 @@ impl tokenizer_trait::Token for CStringLiteral {
                 return None;
             }
             if chr == '"' {
-                if let Some(suffix) = Suffix::parse_token(data.clone()) {
+                let source = data.slice_from(start).into();
+                let content = CString::new(content).ok()?;
+                if let Some(suffix) = Suffix::parse_token(data) {
                     return Some((
-                        Self { value: content, suffix: Some(suffix.0) },
+                        Self { value: content, source, suffix: Some(suffix.0) },
                         suffix.1,
                     ));
                 }
                 return Some((
-                    Self { value: content, suffix: None },
+                    Self { value: content, source, suffix: None },
                     data,
                 ));
             }
-            content.extend_from_slice(chr.to_string().as_bytes());
+            content.extend_from_slice(chr.encode_utf8(&mut [0; 4]).as_bytes());
         }
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use tokenizer_trait::Token;
+
+    use super::*;
+    use crate::{TokenizeOptions, lex_error::LexErrorKind, span::FileId, tokenize};
+
+    /// `src` lexed as a single C string literal
+    fn c_string(src: &str) -> CStringLiteral {
+        let (literal, rest) = CStringLiteral::parse_token(SrcIterator::new(src)).unwrap();
+        assert_eq!(rest.rest(), "", "{src:?} isn't a single literal");
+        literal
+    }
+
+    /// The only error in `src`
+    fn error(src: &str) -> (LexErrorKind, Range<usize>) {
+        let options = TokenizeOptions::default();
+        let error = tokenize(SrcIterator::new(src), FileId::default(), &options).unwrap_err();
+        assert_eq!(error.errors.len(), 1, "{:?}", error.errors);
+        (error.errors[0].kind, error.errors[0].span.byte_range())
+    }
+
+    #[test]
+    fn byte_escapes_are_raw_bytes() {
+        let literal = c_string(r#"c"\x80\xff\x7f\n""#);
+        assert_eq!(literal.value().to_bytes(), b"\x80\xff\x7f\n");
+        assert_eq!(literal.bytes_with_nul(), b"\x80\xff\x7f\n\0");
+    }
+
+    #[test]
+    fn unicode_is_utf8() {
+        let literal = c_string(r#"c"\u{e9}\u{1F600}""#);
+        assert_eq!(literal.value().to_bytes(), "é😀".as_bytes());
+        assert_eq!(c_string("c\"é\"").value().to_bytes(), "é".as_bytes());
+        // a byte escape and the char it would be in a string aren't the same bytes
+        assert_ne!(
+            c_string(r#"c"\xe9""#).value(),
+            c_string(r#"c"\u{e9}""#).value()
+        );
+    }
+
+    #[test]
+    fn bytes_with_nul() {
+        assert_eq!(c_string(r#"c"""#).bytes_with_nul(), b"\0");
+        assert_eq!(c_string(r#"c"ab""#).bytes_with_nul(), b"ab\0");
+        assert_eq!(c_string(r#"c"ab""#).source(), r#"c"ab""#);
+    }
+
+    #[test]
+    fn nul_is_rejected() {
+        for src in [r#"c"a\0b""#, r#"c"a\x00b""#, r#"c"a\u{0}b""#, "c\"a\0b\""] {
+            assert!(CStringLiteral::parse_token(SrcIterator::new(src)).is_none());
+        }
+        assert_eq!(error(r#"x c"a\0b" y"#), (LexErrorKind::NulInCString, 5..7));
+        assert_eq!(error(r#"c"a\x00b""#), (LexErrorKind::NulInCString, 3..7));
+        assert_eq!(error(r#"c"a\u{0}b""#), (LexErrorKind::NulInCString, 3..8));
+        assert_eq!(error("c\"a\0b\""), (LexErrorKind::NulInCString, 3..4));
+    }
+}
@@ -5,27 +5,45 @@ use crate::{string_escapes::UnicodeEscape, suffix::Suffix};
 #[derive(Debug)]
 pub struct CharLiteral {
     value: char,
+    source: Box<str>,
     suffix: Option<Suffix>,
 }
 
+impl CharLiteral {
+    /// The char with escapes decoded
+    pub fn value(&self) -> char {
+        self.value
+    }
+
+    /// The literal as written, quotes included and suffix excluded
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+}
+
 impl tokenizer_trait::Token for CharLiteral {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let start = data.offset();
         if data.next()? != '\'' {
             return None;
         }
 
         let end_chr: char;
-        if *data.peek()? == '\\' {
+        if data.peek()? == '\\' {
             //byte escape
             if let Some(byte_escape) =
-                crate::string_escapes::ByteEscape::parse_token(data.clone())
+                crate::string_escapes::ByteEscape::parse_token(data)
             {
                 if byte_escape.0.value() >= 0x80 {
                     return None;
                 }
                 end_chr = byte_escape.0.value() as char;
                 data = byte_escape.1;
-            } else if let Some(unicode_escape) = UnicodeEscape::parse_token(data.clone()) {
+            } else if let Some(unicode_escape) = UnicodeEscape::parse_token(data) {
                 data = unicode_escape.1;
                 end_chr = unicode_escape.0.to_char()?;
             } else {
@@ -39,9 +57,6 @@ impl tokenizer_trait::Token for CharLiteral {
             }
         } else {
             let chr = data.next()?;
-            if !chr.is_ascii() {
-                return None;
-            }
             if chr == '\r' || chr == '\n' || chr == '\t' || chr == '\'' {
                 return None;
             }
@@ -51,10 +66,12 @@ impl tokenizer_trait::Token for CharLiteral {
         if data.next()? != '\'' {
             return None;
         }
-        if let Some(suffix) = Suffix::parse_token(data.clone()) {
+        let source = data.slice_from(start).into();
+        if let Some(suffix) = Suffix::parse_token(data) {
             return Some((
                 Self {
                     value: end_chr,
+                    source,
                     suffix: Some(suffix.0),
                 },
                 suffix.1,
@@ -63,6 +80,7 @@ impl tokenizer_trait::Token for CharLiteral {
         Some((
             Self {
                 value: end_chr,
+                source,
                 suffix: None,
             },
             data,
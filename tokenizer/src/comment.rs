@@ -0,0 +1,296 @@
+use tokenizer_macro::ParseEnumToken;
+use tokenizer_trait::SrcIterator;
+
+/// An ordinary, non-doc comment. Only kept in the token stream when asked for, see
+/// [`crate::TokenizeOptions::keep_comments`].
+#[derive(ParseEnumToken, Debug)]
+pub enum Comment {
+    LineComment(LineComment),
+    BlockComment(BlockComment),
+}
+
+impl Comment {
+    pub fn text(&self) -> &str {
+        match self {
+            Self::LineComment(comment) => comment.text(),
+            Self::BlockComment(comment) => comment.text(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct LineComment {
+    text: Box<str>,
+}
+
+impl LineComment {
+    /// Everything after the `//`, without the line ending
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl tokenizer_trait::Token for LineComment {
+    fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        if !data.eat("//") {
+            return None;
+        }
+        let start = data.offset();
+        data.eat_while(|chr| chr != '\n');
+        let text = data.slice_from(start);
+        if doc_style(text, DocCommentKind::Line).is_some() && find_bare_cr(text).is_some() {
+            return None;
+        }
+        let text = text.into();
+        Some((Self { text }, data))
+    }
+}
+
+#[derive(Debug)]
+pub struct BlockComment {
+    text: Box<str>,
+}
+
+impl BlockComment {
+    /// Everything between `/*` and `*/`
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl tokenizer_trait::Token for BlockComment {
+    fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let (text, data) = scan_block_comment(data)?;
+        if doc_style(text, DocCommentKind::Block).is_some() && find_bare_cr(text).is_some() {
+            return None;
+        }
+        let text = text.into();
+        Some((Self { text }, data))
+    }
+}
+
+/// Reads a possibly nested block comment and returns everything between the outermost `/*` and
+/// `*/`. `None` if `data` doesn't start a block comment or the comment is never closed.
+pub(crate) fn scan_block_comment(mut data: SrcIterator<'_>) -> Option<(&str, SrcIterator<'_>)> {
+    if !data.eat("/*") {
+        return None;
+    }
+    let start = data.offset();
+    let mut depth = 1;
+    loop {
+        let end = data.offset();
+        if data.eat("/*") {
+            depth += 1;
+        } else if data.eat("*/") {
+            depth -= 1;
+            if depth == 0 {
+                return Some((&data.src()[start..end], data));
+            }
+        } else {
+            data.next()?;
+        }
+    }
+}
+
+/// Byte offset of the first CR that isn't part of a CRLF. Doc comments can't contain those.
+pub(crate) fn find_bare_cr(text: &str) -> Option<usize> {
+    text.match_indices('\r')
+        .map(|(i, _)| i)
+        .find(|i| !text[i + 1..].starts_with('\n'))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocStyle {
+    /// `///` and `/** */`, documents the item that follows
+    Outer,
+    /// `//!` and `/*! */`, documents the enclosing item
+    Inner,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocCommentKind {
+    Line,
+    Block,
+}
+
+/// `///`, `//!`, `/** */` or `/*! */`. Equivalent to a `#[doc = "..."]` (or `#![doc = "..."]`)
+/// attribute with [`DocComment::text`] as the value.
+#[derive(Debug)]
+pub struct DocComment {
+    style: DocStyle,
+    kind: DocCommentKind,
+    text: Box<str>,
+}
+
+impl DocComment {
+    /// Doc comment that stands for a `#[doc = "..."]` attribute with `text` as the value
+    pub(crate) fn new(style: DocStyle, kind: DocCommentKind, text: &str) -> Self {
+        Self {
+            style,
+            kind,
+            text: text.into(),
+        }
+    }
+
+    pub fn style(&self) -> DocStyle {
+        self.style
+    }
+
+    pub fn kind(&self) -> DocCommentKind {
+        self.kind
+    }
+
+    /// The doc text, without the comment markers. This is the string the comment desugars to.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl tokenizer_trait::Token for DocComment {
+    fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let (comment, data) = Comment::parse_token(data)?;
+        let (kind, text) = match &comment {
+            Comment::LineComment(comment) => (DocCommentKind::Line, comment.text()),
+            Comment::BlockComment(comment) => (DocCommentKind::Block, comment.text()),
+        };
+        let style = doc_style(text, kind)?;
+
+        Some((
+            Self {
+                style,
+                kind,
+                text: text[1..].into(),
+            },
+            data,
+        ))
+    }
+}
+
+/// Doc style of a comment, given its text without the leading `//` or `/*`
+fn doc_style(text: &str, kind: DocCommentKind) -> Option<DocStyle> {
+    let marker = match kind {
+        DocCommentKind::Line => '/',
+        DocCommentKind::Block => '*',
+    };
+
+    let mut chars = text.chars();
+    match chars.next()? {
+        '!' => Some(DocStyle::Inner),
+        // `////` and `/***` are ordinary comments, and so are `/**/` and `/***/`
+        chr if chr == marker && chars.next().is_some_and(|chr| chr != marker) => {
+            Some(DocStyle::Outer)
+        }
+        // `///` on its own is still a doc comment
+        chr if chr == marker && kind == DocCommentKind::Line && text.len() == 1 => {
+            Some(DocStyle::Outer)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokenizer_trait::Token;
+
+    use super::*;
+
+    /// Style, kind and text of the doc comment `src` starts with
+    fn doc(src: &str) -> Option<(DocStyle, DocCommentKind, String)> {
+        let (doc, _) = DocComment::parse_token(SrcIterator::new(src))?;
+        Some((doc.style(), doc.kind(), doc.text().to_string()))
+    }
+
+    fn outer(kind: DocCommentKind, text: &str) -> Option<(DocStyle, DocCommentKind, String)> {
+        Some((DocStyle::Outer, kind, text.to_string()))
+    }
+
+    fn inner(kind: DocCommentKind, text: &str) -> Option<(DocStyle, DocCommentKind, String)> {
+        Some((DocStyle::Inner, kind, text.to_string()))
+    }
+
+    #[test]
+    fn line_doc_styles() {
+        use DocCommentKind::Line;
+        assert_eq!(doc("/// a\nb"), outer(Line, " a"));
+        assert_eq!(doc("///"), outer(Line, ""));
+        assert_eq!(doc("//! a"), inner(Line, " a"));
+        assert_eq!(doc("//!"), inner(Line, ""));
+        assert_eq!(doc("//!/ a"), inner(Line, "/ a"));
+        assert_eq!(doc("//// a"), None);
+        assert_eq!(doc("////"), None);
+        assert_eq!(doc("// a"), None);
+        assert_eq!(doc("//"), None);
+    }
+
+    #[test]
+    fn block_doc_styles() {
+        use DocCommentKind::Block;
+        assert_eq!(doc("/** a */"), outer(Block, " a "));
+        assert_eq!(doc("/**a*/"), outer(Block, "a"));
+        assert_eq!(doc("/*! a */"), inner(Block, " a "));
+        assert_eq!(doc("/*!*/"), inner(Block, ""));
+        assert_eq!(doc("/**/"), None);
+        assert_eq!(doc("/***/"), None);
+        assert_eq!(doc("/*** a */"), None);
+        assert_eq!(doc("/* a */"), None);
+    }
+
+    #[test]
+    fn ordinary_comments_keep_their_text() {
+        let (comment, rest) = Comment::parse_token(SrcIterator::new("//// a\nb")).unwrap();
+        assert_eq!(comment.text(), "// a");
+        assert_eq!(rest.rest(), "\nb");
+        let (comment, _) = Comment::parse_token(SrcIterator::new("/***/")).unwrap();
+        assert_eq!(comment.text(), "*");
+    }
+
+    /// Tokens of `src` with ordinary comments kept, as their text or kind, and the errors
+    fn lexed(src: &str) -> (Vec<String>, Vec<crate::lex_error::LexError>) {
+        let options = crate::TokenizeOptions {
+            keep_comments: true,
+            ..Default::default()
+        };
+        let file = crate::span::FileId::default();
+        let (tokens, errors) = match crate::tokenize(SrcIterator::new(src), file, &options) {
+            Ok(tokens) => (tokens, Box::default()),
+            Err(error) => (error.tokens, error.errors),
+        };
+        let tokens = tokens
+            .iter()
+            .map(|token| match &token.kind {
+                crate::TokenKind::Comment(comment) => format!("comment {:?}", comment.text()),
+                crate::TokenKind::DocComment(doc) => format!("doc {:?}", doc.text()),
+                _ => src[token.span.byte_range()].to_string(),
+            })
+            .collect();
+        (tokens, errors.into_vec())
+    }
+
+    #[test]
+    fn nested_block_comments() {
+        let (tokens, errors) = lexed("a /* x /* y */ z */ b");
+        assert_eq!(tokens, ["a", "comment \" x /* y */ z \"", "b"]);
+        assert!(errors.is_empty());
+        let (tokens, _) = lexed("/*/* /* */ */*/");
+        assert_eq!(tokens, ["comment \"/* /* */ */\""]);
+        let (tokens, _) = lexed("/** a /* b */ c */");
+        assert_eq!(tokens, ["doc \" a /* b */ c \""]);
+        // closing more than was opened leaves the rest as punctuation
+        let (tokens, _) = lexed("/* a */ */");
+        assert_eq!(tokens, ["comment \" a \"", "*", "/"]);
+    }
+
+    #[test]
+    fn unterminated_nested_block_comment() {
+        let src = "x /* a /* b */\nc";
+        let (tokens, errors) = lexed(src);
+        assert_eq!(tokens, ["x", "/* a /* b */\nc"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            crate::lex_error::LexErrorKind::UnterminatedBlockComment
+        );
+        assert_eq!(errors[0].span.byte_range(), 2..src.len());
+        assert_eq!((errors[0].span.end.line, errors[0].span.end.column), (2, 2));
+    }
+}
@@ -0,0 +1,867 @@
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    io::{self, Write},
+};
+
+use tokenizer_trait::{Edition, SrcIterator, Token as TokenTrait};
+
+use crate::{
+    Token, TokenKind, frontmatter,
+    lex_error::LexErrorKind,
+    line_endings::NormalizedSrc,
+    punctuation::Spacing,
+    span::{FileId, Pos, Span},
+    string_literal::StringLiteral,
+};
+
+/// Token kinds as dumps name them. Binary dumps number them by their position here.
+const KINDS: &[&str] = &[
+    "DocComment",
+    "Comment",
+    "ReservedToken",
+    "RawIdentifier",
+    "CharLiteral",
+    "StringLiteral",
+    "RawStringLiteral",
+    "ByteLiteral",
+    "ByteStringLiteral",
+    "RawByteStringLiteral",
+    "CStringLiteral",
+    "RawCStringLiteral",
+    "FloatLiteral",
+    "IntegerLiteral",
+    "LifetimeToken",
+    "Punctuation",
+    "Keyword",
+    "IdentifierOrKeyword",
+    "Shebang",
+    "Frontmatter",
+    "Error",
+    "EndOfFile",
+];
+
+/// Error kinds numbered the same way. [`LexErrorKind::StrayCharacter`] gets its char back from
+/// the token text.
+const ERROR_KINDS: &[LexErrorKind] = &[
+    LexErrorKind::UnterminatedBlockComment,
+    LexErrorKind::BareCrInDocComment,
+    LexErrorKind::BareCrInString,
+    LexErrorKind::BareCrInRawString,
+    LexErrorKind::BareCrInCharLiteral,
+    LexErrorKind::UnterminatedString,
+    LexErrorKind::UnterminatedRawString,
+    LexErrorKind::UnterminatedCharLiteral,
+    LexErrorKind::InvalidEscape,
+    LexErrorKind::NulInCString,
+    LexErrorKind::InvalidLiteral,
+    LexErrorKind::ReservedPrefix,
+    LexErrorKind::ReservedNumber,
+    LexErrorKind::ReservedPounds,
+    LexErrorKind::ReservedGuardedString,
+    LexErrorKind::ReservedRawIdentifier,
+    LexErrorKind::ReservedRawLifetime,
+    LexErrorKind::UnclosedFrontmatter,
+    LexErrorKind::FrontmatterFenceMismatch,
+    LexErrorKind::FrontmatterNotAtLineStart,
+    LexErrorKind::InvalidFrontmatterInfostring,
+    LexErrorKind::TextAfterFrontmatterFence,
+    LexErrorKind::MultipleFrontmatter,
+    LexErrorKind::StrayCharacter('\0'),
+    // never on an error token, but numbered all the same. Added after the rest, so older binary
+    // dumps keep their numbers.
+    LexErrorKind::InvalidIntegerSuffix,
+    LexErrorKind::InvalidFloatSuffix,
+    LexErrorKind::NonDecimalFloatLiteral(2),
+    LexErrorKind::InvalidLiteralSuffix(""),
+    LexErrorKind::ExponentInTupleIndex,
+    LexErrorKind::SuffixOnTupleIndex,
+];
+
+fn kind_name(kind: &TokenKind) -> &'static str {
+    match kind {
+        TokenKind::DocComment(_) => "DocComment",
+        TokenKind::Comment(_) => "Comment",
+        TokenKind::ReservedToken(_) => "ReservedToken",
+        TokenKind::RawIdentifier(_) => "RawIdentifier",
+        TokenKind::CharLiteral(_) => "CharLiteral",
+        TokenKind::StringLiteral(_) => "StringLiteral",
+        TokenKind::RawStringLiteral(_) => "RawStringLiteral",
+        TokenKind::ByteLiteral(_) => "ByteLiteral",
+        TokenKind::ByteStringLiteral(_) => "ByteStringLiteral",
+        TokenKind::RawByteStringLiteral(_) => "RawByteStringLiteral",
+        TokenKind::CStringLiteral(_) => "CStringLiteral",
+        TokenKind::RawCStringLiteral(_) => "RawCStringLiteral",
+        TokenKind::FloatLiteral(_) => "FloatLiteral",
+        TokenKind::IntegerLiteral(_) => "IntegerLiteral",
+        TokenKind::LifetimeToken(_) => "LifetimeToken",
+        TokenKind::Punctuation(_) => "Punctuation",
+        TokenKind::Keyword(_) => "Keyword",
+        TokenKind::IdentifierOrKeyword(_) => "IdentifierOrKeyword",
+        TokenKind::Shebang(_) => "Shebang",
+        TokenKind::Frontmatter(_) => "Frontmatter",
+        TokenKind::Error(_) => "Error",
+        TokenKind::EndOfFile => "EndOfFile",
+    }
+}
+
+fn error_name(kind: &LexErrorKind) -> String {
+    let mut name = format!("{:?}", kind);
+    name.truncate(name.find('(').unwrap_or(name.len()));
+    name
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpErrorKind {
+    /// Not laid out the way the format is
+    Malformed,
+    UnknownKind,
+    /// The text doesn't lex back to the kind it was dumped as
+    KindMismatch,
+}
+
+impl Display for DumpErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed token dump"),
+            Self::UnknownKind => write!(f, "unknown token kind"),
+            Self::KindMismatch => write!(f, "token text doesn't lex to its recorded kind"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DumpError {
+    pub kind: DumpErrorKind,
+    /// 1-based line of a text or JSON dump, index of the token in a binary dump
+    pub record: usize,
+}
+
+impl Display for DumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "record {}: {}", self.record, self.kind)
+    }
+}
+
+impl std::error::Error for DumpError {}
+
+/// A token as a dump stores it. Kinds are only stored by name, reading a dump lexes the text again.
+struct Record<'a> {
+    kind: &'a str,
+    error: Option<String>,
+    text: Cow<'a, str>,
+    start: Pos,
+    end: Pos,
+    spacing: Spacing,
+}
+
+impl<'a> Record<'a> {
+    fn new(token: &Token, src: &'a str) -> Self {
+        Self {
+            kind: kind_name(&token.kind),
+            error: match &token.kind {
+                TokenKind::Error(kind) => Some(error_name(kind)),
+                _ => None,
+            },
+            text: Cow::Borrowed(&src[token.span.byte_range()]),
+            start: token.span.start,
+            end: token.span.end,
+            spacing: token.spacing,
+        }
+    }
+
+    fn rebuild(&self, file: FileId, edition: Edition) -> Result<Token, DumpErrorKind> {
+        if !KINDS.contains(&self.kind) {
+            return Err(DumpErrorKind::UnknownKind);
+        }
+        // lexed the same way `tokenize` lexes it
+        let normalized = NormalizedSrc::new(&self.text);
+        let text = normalized.text();
+        let data = SrcIterator::new(text).with_edition(edition);
+        let kind = match (self.kind, self.error.as_deref()) {
+            ("Error", Some(error)) => {
+                let kind = ERROR_KINDS
+                    .iter()
+                    .find(|kind| error_name(kind) == error)
+                    .ok_or(DumpErrorKind::UnknownKind)?;
+                match kind {
+                    LexErrorKind::StrayCharacter(_) => {
+                        let chr = text.chars().next().ok_or(DumpErrorKind::KindMismatch)?;
+                        TokenKind::Error(LexErrorKind::StrayCharacter(chr))
+                    }
+                    kind => TokenKind::Error(*kind),
+                }
+            }
+            (_, Some(_)) | ("Error", None) => return Err(DumpErrorKind::Malformed),
+            ("EndOfFile", None) if text.is_empty() => TokenKind::EndOfFile,
+            ("Shebang", None) if text.starts_with("#!") && !text.contains('\n') => {
+                TokenKind::Shebang(text.into())
+            }
+            ("Frontmatter", None) => match frontmatter::lex_frontmatter(data, true) {
+                (Ok(frontmatter), rest) if rest.peek().is_none() => {
+                    TokenKind::Frontmatter(frontmatter)
+                }
+                _ => return Err(DumpErrorKind::KindMismatch),
+            },
+            (name, None) => match TokenKind::parse_token(data) {
+                Some((kind, rest)) if rest.peek().is_none() && kind_name(&kind) == name => kind,
+                _ => return Err(DumpErrorKind::KindMismatch),
+            },
+        };
+        Ok(Token {
+            kind,
+            span: Span::new(file, self.start, self.end),
+            spacing: self.spacing,
+            lossless: None,
+        })
+    }
+}
+
+/// Writes one token per line, like
+///
+/// ```text
+/// Punctuation 3:9-3:10 41..42 joint "-"
+/// Error(UnterminatedString) 4:1-4:5 50..54 "\"abc"
+/// ```
+///
+/// `src` is the text the tokens were lexed from.
+pub fn write_text(tokens: &[Token], src: &str, out: &mut impl Write) -> io::Result<()> {
+    for token in tokens {
+        let record = Record::new(token, src);
+        write!(out, "{}", record.kind)?;
+        if let Some(error) = &record.error {
+            write!(out, "({})", error)?;
+        }
+        let (start, end) = (record.start, record.end);
+        write!(
+            out,
+            " {}:{}-{}:{} {}..{} ",
+            start.line, start.column, end.line, end.column, start.offset, end.offset
+        )?;
+        if record.spacing == Spacing::Joint {
+            write!(out, "joint ")?;
+        }
+        writeln!(out, "{:?}", record.text)?;
+    }
+    Ok(())
+}
+
+/// Reads back what [`write_text`] wrote. Empty lines and lines starting with `#` are skipped.
+/// The tokens come back without [`crate::trivia::LosslessText`].
+pub fn read_text(dump: &str, file: FileId, edition: Edition) -> Result<Box<[Token]>, DumpError> {
+    dump.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            read_text_line(line)
+                .and_then(|record| record.rebuild(file, edition))
+                .map_err(|kind| DumpError {
+                    kind,
+                    record: i + 1,
+                })
+        })
+        .collect()
+}
+
+fn read_text_line(line: &str) -> Result<Record<'_>, DumpErrorKind> {
+    let mut fields = line.splitn(4, ' ');
+    let (Some(label), Some(lines), Some(offsets), Some(rest)) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        return Err(DumpErrorKind::Malformed);
+    };
+    let (kind, error) = match label
+        .strip_suffix(')')
+        .and_then(|label| label.split_once('('))
+    {
+        Some((kind, error)) => (kind, Some(error.to_string())),
+        None => (label, None),
+    };
+    let (start, end) = lines.split_once('-').ok_or(DumpErrorKind::Malformed)?;
+    let (start_offset, end_offset) = offsets.split_once("..").ok_or(DumpErrorKind::Malformed)?;
+    let (spacing, quoted) = match rest.strip_prefix("joint ") {
+        Some(quoted) => (Spacing::Joint, quoted),
+        None => (Spacing::Alone, rest),
+    };
+    // `{:?}` of a str is a valid string literal
+    let text = match StringLiteral::parse_token(SrcIterator::new(quoted)) {
+        Some((literal, rest)) if rest.peek().is_none() && literal.suffix().is_none() => {
+            Cow::Owned(literal.value().into())
+        }
+        _ => return Err(DumpErrorKind::Malformed),
+    };
+    Ok(Record {
+        kind,
+        error,
+        text,
+        start: text_pos(start_offset, start)?,
+        end: text_pos(end_offset, end)?,
+        spacing,
+    })
+}
+
+fn text_pos(offset: &str, line_column: &str) -> Result<Pos, DumpErrorKind> {
+    let (line, column) = line_column
+        .split_once(':')
+        .ok_or(DumpErrorKind::Malformed)?;
+    let parse = |number: &str| number.parse().map_err(|_| DumpErrorKind::Malformed);
+    Ok(Pos {
+        offset: parse(offset)? as usize,
+        line: parse(line)?,
+        column: parse(column)?,
+    })
+}
+
+/// Writes one JSON object per line, positions as `[offset, line, column]`:
+///
+/// ```text
+/// {"kind":"Punctuation","spacing":"joint","start":[41,3,9],"end":[42,3,10],"text":"-"}
+/// {"kind":"Error","error":"UnterminatedString","spacing":"alone","start":[50,4,1],"end":[54,4,5],"text":"\"abc"}
+/// ```
+pub fn write_json_lines(tokens: &[Token], src: &str, out: &mut impl Write) -> io::Result<()> {
+    for token in tokens {
+        let record = Record::new(token, src);
+        write!(out, "{{\"kind\":\"{}\"", record.kind)?;
+        if let Some(error) = &record.error {
+            write!(out, ",\"error\":\"{}\"", error)?;
+        }
+        let spacing = match record.spacing {
+            Spacing::Joint => "joint",
+            Spacing::Alone => "alone",
+        };
+        let (start, end) = (record.start, record.end);
+        writeln!(
+            out,
+            ",\"spacing\":\"{}\",\"start\":[{},{},{}],\"end\":[{},{},{}],\"text\":{}}}",
+            spacing,
+            start.offset,
+            start.line,
+            start.column,
+            end.offset,
+            end.line,
+            end.column,
+            json_string(&record.text)
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a `{"file":"..."}` line, which [`read_json_lines`] skips. Tells files apart when the
+/// dumps of several go into one stream.
+pub fn write_json_file_line(path: &str, out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "{{\"file\":{}}}", json_string(path))
+}
+
+/// Reads back what [`write_json_lines`] wrote. Empty lines and objects with a `"file"` key are
+/// skipped.
+pub fn read_json_lines(
+    dump: &str,
+    file: FileId,
+    edition: Edition,
+) -> Result<Box<[Token]>, DumpError> {
+    let mut tokens = Vec::new();
+    for (i, line) in dump.lines().enumerate() {
+        let error = |kind| DumpError {
+            kind,
+            record: i + 1,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let object = JsonObject::parse(line).ok_or(error(DumpErrorKind::Malformed))?;
+        if object.get("file").is_some() {
+            continue;
+        }
+        let token = read_json_object(&object)
+            .and_then(|record| record.rebuild(file, edition))
+            .map_err(error)?;
+        tokens.push(token);
+    }
+    Ok(tokens.into_boxed_slice())
+}
+
+fn read_json_object(object: &JsonObject) -> Result<Record<'_>, DumpErrorKind> {
+    let string = |key| match object.get(key) {
+        Some(JsonValue::String(string)) => Ok(string.as_str()),
+        _ => Err(DumpErrorKind::Malformed),
+    };
+    let pos = |key| match object.get(key) {
+        Some(JsonValue::Numbers(numbers)) if numbers.len() == 3 => Ok(Pos {
+            offset: numbers[0] as usize,
+            line: numbers[1] as u32,
+            column: numbers[2] as u32,
+        }),
+        _ => Err(DumpErrorKind::Malformed),
+    };
+    let spacing = match string("spacing")? {
+        "joint" => Spacing::Joint,
+        "alone" => Spacing::Alone,
+        _ => return Err(DumpErrorKind::Malformed),
+    };
+    Ok(Record {
+        kind: string("kind")?,
+        error: string("error").ok().map(str::to_string),
+        text: Cow::Borrowed(string("text")?),
+        start: pos("start")?,
+        end: pos("end")?,
+        spacing,
+    })
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for chr in text.chars() {
+        match chr {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            chr if (chr as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", chr as u32)),
+            chr => out.push(chr),
+        }
+    }
+    out.push('"');
+    out
+}
+
+enum JsonValue {
+    String(String),
+    Numbers(Vec<u64>),
+}
+
+/// Just enough JSON for the flat objects [`write_json_lines`] writes: string values and arrays of
+/// numbers
+struct JsonObject {
+    fields: Vec<(String, JsonValue)>,
+}
+
+impl JsonObject {
+    fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut rest = line.trim().strip_prefix('{')?.trim_start();
+        let mut fields = Vec::new();
+        if let Some(after) = rest.strip_prefix('}') {
+            return after.is_empty().then_some(Self { fields });
+        }
+        loop {
+            let (key, after) = json_parse_string(rest)?;
+            rest = after.trim_start().strip_prefix(':')?.trim_start();
+            let value = if rest.starts_with('"') {
+                let (string, after) = json_parse_string(rest)?;
+                rest = after;
+                JsonValue::String(string)
+            } else {
+                let (numbers, after) = rest.strip_prefix('[')?.split_once(']')?;
+                rest = after;
+                let numbers = numbers
+                    .split(',')
+                    .map(|number| number.trim().parse().ok())
+                    .collect::<Option<_>>()?;
+                JsonValue::Numbers(numbers)
+            };
+            fields.push((key, value));
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after.trim_start();
+            } else {
+                return (rest.strip_prefix('}')?.trim().is_empty()).then_some(Self { fields });
+            }
+        }
+    }
+}
+
+/// Parses the string at the start of `text` and returns it with what follows it
+fn json_parse_string(text: &str) -> Option<(String, &str)> {
+    let mut rest = text.strip_prefix('"')?;
+    let mut out = String::new();
+    loop {
+        let mut chars = rest.chars();
+        let chr = chars.next()?;
+        rest = chars.as_str();
+        if chr == '"' {
+            return Some((out, rest));
+        }
+        if chr != '\\' {
+            out.push(chr);
+            continue;
+        }
+        let mut chars = rest.chars();
+        let escape = chars.next()?;
+        rest = chars.as_str();
+        let escaped = match escape {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'u' => {
+                let (unit, after) = json_hex(rest)?;
+                rest = after;
+                let code = if (0xD800..0xDC00).contains(&unit) {
+                    // the low half of a surrogate pair follows
+                    let (low, after) = json_hex(rest.strip_prefix("\\u")?)?;
+                    rest = after;
+                    0x10000 + ((unit - 0xD800) << 10) + low.checked_sub(0xDC00)?
+                } else {
+                    unit
+                };
+                char::from_u32(code)?
+            }
+            chr => chr,
+        };
+        out.push(escaped);
+    }
+}
+
+/// The 4 hex digits of a `\u` escape
+fn json_hex(text: &str) -> Option<(u32, &str)> {
+    let digits = text.get(..4)?;
+    Some((u32::from_str_radix(digits, 16).ok()?, &text[4..]))
+}
+
+const BINARY_MAGIC: &[u8; 4] = b"TOKS";
+const BINARY_VERSION: u32 = 1;
+const HEADER_LEN: usize = 16;
+/// Every field of a record is a little-endian u32
+const RECORD_FIELDS: usize = 9;
+const RECORD_LEN: usize = RECORD_FIELDS * 4;
+
+/// Writes a binary dump: a 16 byte header (magic, version, token count, text length), then a
+/// fixed size record for every token, then the text of all tokens. All numbers are little-endian
+/// u32s. Records being fixed size, a memory-mapped dump can be read one token at a time with
+/// [`BinaryDump`].
+pub fn write_binary(tokens: &[Token], src: &str, out: &mut impl Write) -> io::Result<()> {
+    let mut records = Vec::with_capacity(tokens.len() * RECORD_LEN);
+    let mut text = String::new();
+    for token in tokens {
+        let record = Record::new(token, src);
+        let kind = KINDS.iter().position(|kind| *kind == record.kind).unwrap();
+        // 0 for no error
+        let error = record.error.as_ref().map_or(0, |name| {
+            1 + ERROR_KINDS
+                .iter()
+                .position(|kind| error_name(kind) == *name)
+                .unwrap()
+        });
+        let spacing = (record.spacing == Spacing::Joint) as usize;
+        let fields = [
+            kind | error << 8 | spacing << 16,
+            record.start.offset,
+            record.start.line as usize,
+            record.start.column as usize,
+            record.end.offset,
+            record.end.line as usize,
+            record.end.column as usize,
+            text.len(),
+            record.text.len(),
+        ];
+        for field in fields {
+            records.extend_from_slice(&(field as u32).to_le_bytes());
+        }
+        text.push_str(&record.text);
+    }
+
+    out.write_all(BINARY_MAGIC)?;
+    out.write_all(&BINARY_VERSION.to_le_bytes())?;
+    out.write_all(&(tokens.len() as u32).to_le_bytes())?;
+    out.write_all(&(text.len() as u32).to_le_bytes())?;
+    out.write_all(&records)?;
+    out.write_all(text.as_bytes())
+}
+
+/// A binary dump read in place, for example from a memory-mapped file. Tokens are only rebuilt
+/// when asked for.
+pub struct BinaryDump<'a> {
+    records: &'a [u8],
+    text: &'a str,
+}
+
+impl<'a> BinaryDump<'a> {
+    /// Checks the header and that the sizes add up. `record` in the error is 0 for a bad header.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DumpError> {
+        let malformed = DumpError {
+            kind: DumpErrorKind::Malformed,
+            record: 0,
+        };
+        if bytes.len() < HEADER_LEN || &bytes[..4] != BINARY_MAGIC {
+            return Err(malformed);
+        }
+        let header = |i: usize| u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+        let (version, count, text_len) = (header(1), header(2) as usize, header(3) as usize);
+        let records_end = HEADER_LEN + count * RECORD_LEN;
+        if version != BINARY_VERSION || bytes.len() != records_end + text_len {
+            return Err(malformed);
+        }
+        Ok(Self {
+            records: &bytes[HEADER_LEN..records_end],
+            text: std::str::from_utf8(&bytes[records_end..]).map_err(|_| malformed)?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len() / RECORD_LEN
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn token(&self, index: usize, file: FileId, edition: Edition) -> Result<Token, DumpError> {
+        self.record(index)
+            .and_then(|record| record.rebuild(file, edition))
+            .map_err(|kind| DumpError {
+                kind,
+                record: index,
+            })
+    }
+
+    fn record(&self, index: usize) -> Result<Record<'a>, DumpErrorKind> {
+        let bytes = self
+            .records
+            .get(index * RECORD_LEN..(index + 1) * RECORD_LEN)
+            .ok_or(DumpErrorKind::Malformed)?;
+        let field = |i: usize| u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+        let pos = |i: usize| Pos {
+            offset: field(i) as usize,
+            line: field(i + 1),
+            column: field(i + 2),
+        };
+        let tags = field(0);
+        let kind = KINDS
+            .get((tags & 0xff) as usize)
+            .ok_or(DumpErrorKind::UnknownKind)?;
+        let error = match (tags >> 8) & 0xff {
+            0 => None,
+            error => Some(
+                ERROR_KINDS
+                    .get(error as usize - 1)
+                    .ok_or(DumpErrorKind::UnknownKind)?,
+            ),
+        };
+        let spacing = match tags >> 16 {
+            0 => Spacing::Alone,
+            1 => Spacing::Joint,
+            _ => return Err(DumpErrorKind::Malformed),
+        };
+        let (text_start, text_len) = (field(7) as usize, field(8) as usize);
+        let text = self
+            .text
+            .get(text_start..text_start + text_len)
+            .ok_or(DumpErrorKind::Malformed)?;
+        Ok(Record {
+            kind,
+            error: error.map(error_name),
+            text: Cow::Borrowed(text),
+            start: pos(1),
+            end: pos(4),
+            spacing,
+        })
+    }
+}
+
+/// Reads back every token of what [`write_binary`] wrote
+pub fn read_binary(
+    bytes: &[u8],
+    file: FileId,
+    edition: Edition,
+) -> Result<Box<[Token]>, DumpError> {
+    let dump = BinaryDump::new(bytes)?;
+    (0..dump.len())
+        .map(|i| dump.token(i, file, edition))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TokenizeOptions, tokenize};
+
+    const SRC: &str = "#!/usr/bin/env run\r\n---cargo\r\n[dependencies]\r\n---\r\n\
+        /// doc\r\nfn main() -> u8 { 'a: loop {} r#\"raw\"# b'\\n' 1.5e3 x.0 a::<T>>= }\r\n\
+        § \"unterminated";
+
+    fn lexed(src: &str) -> Box<[Token]> {
+        let options = TokenizeOptions {
+            lossless: true,
+            ..TokenizeOptions::default()
+        };
+        match tokenize(SrcIterator::new(src), FileId::default(), &options) {
+            Ok(tokens) => tokens,
+            Err(error) => error.tokens,
+        }
+    }
+
+    /// What a dump keeps of a token
+    fn dumped(tokens: &[Token]) -> Vec<(String, Span, Spacing)> {
+        tokens
+            .iter()
+            .map(|token| (format!("{:?}", token.kind), token.span, token.spacing))
+            .collect()
+    }
+
+    /// The tokens of [`SRC`] and how one of the formats writes them
+    fn sample(
+        write: fn(&[Token], &str, &mut Vec<u8>) -> io::Result<()>,
+    ) -> (Box<[Token]>, Vec<u8>) {
+        let tokens = lexed(SRC);
+        for kind in ["Shebang", "Frontmatter", "DocComment", "Error", "EndOfFile"] {
+            assert!(
+                tokens.iter().any(|token| kind_name(&token.kind) == kind),
+                "{kind}"
+            );
+        }
+        let mut dump = Vec::new();
+        write(&tokens, SRC, &mut dump).unwrap();
+        (tokens, dump)
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let (tokens, dump) = sample(write_text);
+        let dump = std::str::from_utf8(&dump).unwrap();
+        let read_back = read_text(dump, FileId::default(), Edition::Edition2024).unwrap();
+        assert_eq!(dumped(&read_back), dumped(&tokens));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let (tokens, dump) = sample(|tokens, src, out| {
+            write_json_file_line("a \"quoted\" path", out)?;
+            write_json_lines(tokens, src, out)
+        });
+        let dump = std::str::from_utf8(&dump).unwrap();
+        let read_back = read_json_lines(dump, FileId::default(), Edition::Edition2024).unwrap();
+        assert_eq!(dumped(&read_back), dumped(&tokens));
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let (tokens, dump) = sample(write_binary);
+        let read_back = read_binary(&dump, FileId::default(), Edition::Edition2024).unwrap();
+        assert_eq!(dumped(&read_back), dumped(&tokens));
+        // a single token without reading the others
+        let dump = BinaryDump::new(&dump).unwrap();
+        assert_eq!(dump.len(), tokens.len());
+        let last = dump.token(tokens.len() - 1, FileId::default(), Edition::Edition2024);
+        assert_eq!(
+            dumped(&[last.unwrap()]),
+            dumped(&tokens[tokens.len() - 1..])
+        );
+    }
+
+    fn text_error(dump: &str) -> DumpError {
+        read_text(dump, FileId::default(), Edition::Edition2024).unwrap_err()
+    }
+
+    fn error(kind: DumpErrorKind, record: usize) -> DumpError {
+        DumpError { kind, record }
+    }
+
+    #[test]
+    fn malformed_text() {
+        use DumpErrorKind::*;
+        assert_eq!(text_error("Punctuation 1:1-1:2 0..1"), error(Malformed, 1));
+        assert_eq!(
+            text_error("Punctuation 1:1-1:2 0..x \"-\""),
+            error(Malformed, 1)
+        );
+        assert_eq!(
+            text_error("Punctuation 1:1 0..1 \"-\""),
+            error(Malformed, 1)
+        );
+        assert_eq!(
+            text_error("Punctuation 1:1-1:2 0..1 -"),
+            error(Malformed, 1)
+        );
+        assert_eq!(
+            text_error("Punctuation(Oops) 1:1-1:2 0..1 \"-\""),
+            error(Malformed, 1)
+        );
+        assert_eq!(
+            text_error("Error(Oops) 1:1-1:2 0..1 \"-\""),
+            error(UnknownKind, 1)
+        );
+        assert_eq!(
+            text_error("Bogus 1:1-1:2 0..1 \"-\""),
+            error(UnknownKind, 1)
+        );
+        assert_eq!(
+            text_error("Punctuation 1:1-1:3 0..2 \"ab\""),
+            error(KindMismatch, 1)
+        );
+        // comments and empty lines count towards the line number
+        let dump = "# comment\n\nPunctuation 1:1-1:2 0..1 \"-\"\nPunctuation 1:2-1:3 1..2 \"\"";
+        assert_eq!(text_error(dump), error(KindMismatch, 4));
+    }
+
+    #[test]
+    fn malformed_json() {
+        use DumpErrorKind::*;
+        let json_error = |dump: &str| {
+            read_json_lines(dump, FileId::default(), Edition::Edition2024).unwrap_err()
+        };
+        assert_eq!(json_error("{\"kind\":"), error(Malformed, 1));
+        let record = |kind, spacing, text| {
+            format!(
+                "{{\"kind\":\"{kind}\",\"spacing\":\"{spacing}\",\"start\":[0,1,1],\"end\":[1,1,2],\
+                 \"text\":{text}}}"
+            )
+        };
+        assert_eq!(
+            json_error(&record("Punctuation", "apart", "\"-\"")),
+            error(Malformed, 1)
+        );
+        assert_eq!(
+            json_error(&record("Punctuation", "alone", "1")),
+            error(Malformed, 1)
+        );
+        assert_eq!(
+            json_error(&record("Bogus", "alone", "\"-\"")),
+            error(UnknownKind, 1)
+        );
+        let dump = format!(
+            "{{\"file\":\"a.rs\"}}\n{}\n{}",
+            record("Punctuation", "alone", "\"-\""),
+            record("IntegerLiteral", "alone", "\"-\"")
+        );
+        assert_eq!(json_error(&dump), error(KindMismatch, 3));
+    }
+
+    #[test]
+    fn malformed_binary() {
+        use DumpErrorKind::*;
+        let src = "a + b";
+        let mut dump = Vec::new();
+        write_binary(&lexed(src), src, &mut dump).unwrap();
+        let binary_error =
+            |dump: &[u8]| read_binary(dump, FileId::default(), Edition::Edition2024).unwrap_err();
+        assert_eq!(binary_error(&dump[..dump.len() - 1]), error(Malformed, 0));
+        assert_eq!(binary_error(b"TOKZ"), error(Malformed, 0));
+        let mut bad_version = dump.clone();
+        bad_version[4] = 2;
+        assert_eq!(binary_error(&bad_version), error(Malformed, 0));
+
+        let record = |index: usize, field: usize| HEADER_LEN + index * RECORD_LEN + field * 4;
+        let mut bad_kind = dump.clone();
+        bad_kind[record(1, 0)] = 0xff;
+        assert_eq!(binary_error(&bad_kind), error(UnknownKind, 1));
+        let mut bad_text = dump.clone();
+        bad_text[record(2, 7)] = 0xff;
+        assert_eq!(binary_error(&bad_text), error(Malformed, 2));
+        // `+` read back as an identifier
+        let mut mismatch = dump.clone();
+        mismatch[record(1, 0)] = KINDS
+            .iter()
+            .position(|kind| *kind == "IdentifierOrKeyword")
+            .unwrap() as u8;
+        assert_eq!(binary_error(&mismatch), error(KindMismatch, 1));
+    }
+}
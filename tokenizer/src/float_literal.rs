@@ -1,59 +1,73 @@
 use tokenizer_trait::{SrcIterator, Token};
 
+use std::borrow::Cow;
+
 use crate::{
     integer_literal::DecLiteral,
-    suffix::{Suffix, SuffixNoE},
+    suffix::{FloatSuffix, Suffix, SuffixNoE},
 };
 
 #[derive(Debug)]
 pub struct FloatLiteral {
-    whole_part: DecLiteral,
-    fractional_part: Option<DecLiteral>,
-    exponent_part: Option<ExponentPart>,
+    /// The literal as written, without the suffix
+    source: Box<str>,
     suffix: Option<Suffix>,
 }
 
 impl tokenizer_trait::Token for FloatLiteral {
     fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        let (whole_part, data) = DecLiteral::parse_token(data)?;
-
-        if let Some(parsed) = parse_third_form(data.clone()) {
-            return Some((
-                Self {
-                    whole_part,
-                    fractional_part: parsed.1,
-                    exponent_part: Some(parsed.2),
-                    suffix: parsed.3,
-                },
-                parsed.0,
-            ));
-        }
+        let start = data.offset();
+        let (_, data) = DecLiteral::parse_token(data)?;
 
-        if let Some(parsed) = parse_second_form(data.clone()) {
-            return Some((
-                Self {
-                    whole_part,
-                    fractional_part: Some(parsed.1),
-                    exponent_part: None,
-                    suffix: parsed.2.map(|s| s.into_generic_suffix()),
-                },
-                parsed.0,
-            ));
-        }
+        let (suffix, data) = if let Some(parsed) = parse_third_form(data) {
+            (parsed.1, parsed.0)
+        } else if let Some(parsed) = parse_second_form(data) {
+            (parsed.1.map(|s| s.into_generic_suffix()), parsed.0)
+        } else {
+            (None, parse_first_form(data)?)
+        };
+
+        let text = data.slice_from(start);
+        let suffix_len = suffix.as_ref().map_or(0, Suffix::written_len);
+        Some((
+            Self {
+                source: text[..text.len() - suffix_len].into(),
+                suffix,
+            },
+            data,
+        ))
+    }
+}
 
-        if let Some(parsed) = parse_first_form(data.clone()) {
-            return Some((
-                Self {
-                    whole_part,
-                    fractional_part: None,
-                    exponent_part: None,
-                    suffix: None,
-                },
-                parsed,
-            ));
+impl FloatLiteral {
+    /// Value of the literal, correctly rounded. With an `f32` suffix it is rounded to `f32`
+    /// first, so the result is exactly the `f32` the literal stands for.
+    pub fn value(&self) -> f64 {
+        // the lexer only accepts what `str::parse` does once the separators are gone, `1.` and
+        // `1E+5` included
+        let text = if self.source.contains('_') {
+            Cow::Owned(self.source.replace('_', ""))
+        } else {
+            Cow::Borrowed(&*self.source)
+        };
+        match self.float_suffix() {
+            Some(FloatSuffix::F32) => text.parse::<f32>().unwrap() as f64,
+            _ => text.parse::<f64>().unwrap(),
         }
+    }
+
+    /// The literal as written, without the suffix
+    pub fn source(&self) -> &str {
+        &self.source
+    }
 
-        None
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+
+    /// `None` without a suffix, and for suffixes that aren't float types
+    pub fn float_suffix(&self) -> Option<FloatSuffix> {
+        FloatSuffix::from_suffix(self.suffix()?)
     }
 }
 
@@ -64,89 +78,112 @@ fn parse_first_form(mut data: SrcIterator) -> Option<SrcIterator> {
     let Some(next) = data.peek() else {
         return Some(data);
     };
-    if *next == '.' || *next == '_' || unicode_ident::is_xid_start(*next) {
+    if next == '.' || next == '_' || unicode_ident::is_xid_start(next) {
         return None;
     }
     Some(data)
 }
 
-fn parse_second_form(
-    mut data: SrcIterator,
-) -> Option<(SrcIterator, DecLiteral, Option<SuffixNoE>)> {
+fn parse_second_form(mut data: SrcIterator) -> Option<(SrcIterator, Option<SuffixNoE>)> {
     if data.next()? != '.' {
         return None;
     }
-    let (fractional_part, data) = DecLiteral::parse_token(data)?;
-    if let Some(a) = SuffixNoE::parse_token(data.clone()) {
-        return Some((a.1, fractional_part, Some(a.0)));
+    let (_, data) = DecLiteral::parse_token(data)?;
+    if let Some(a) = SuffixNoE::parse_token(data) {
+        return Some((a.1, Some(a.0)));
     }
-    Some((data, fractional_part, None))
+    Some((data, None))
 }
 
-fn parse_third_form(
-    mut data: SrcIterator,
-) -> Option<(
-    SrcIterator,
-    Option<DecLiteral>,
-    ExponentPart,
-    Option<Suffix>,
-)> {
-    let data_cont;
-    let fractional_part;
-    if *data.peek()? == '.' {
+fn parse_third_form(mut data: SrcIterator) -> Option<(SrcIterator, Option<Suffix>)> {
+    if data.peek()? == '.' {
         //parse fractional part
         data.next();
-        let (_fractional_part, data) = DecLiteral::parse_token(data)?;
-        data_cont = data;
-        fractional_part = Some(_fractional_part);
-    } else {
-        data_cont = data;
-        fractional_part = None;
+        data = DecLiteral::parse_token(data)?.1;
     }
 
-    let exponent_part = ExponentPart::parse_token(data_cont)?;
+    let data = parse_exponent(data)?;
 
-    if let Some(suffix) = Suffix::parse_token(exponent_part.1.clone()) {
-        return Some((suffix.1, fractional_part, exponent_part.0, Some(suffix.0)));
+    if let Some(suffix) = Suffix::parse_token(data) {
+        return Some((suffix.1, Some(suffix.0)));
     }
-    Some((exponent_part.1, fractional_part, exponent_part.0, None))
+    Some((data, None))
 }
 
-#[derive(Debug)]
-struct ExponentPart {
-    sign: Option<bool>, //true for +, false for -
-    exponent: DecLiteral,
+fn parse_exponent(mut data: SrcIterator) -> Option<SrcIterator> {
+    let marker = data.next()?;
+    if marker != 'e' && marker != 'E' {
+        return None;
+    }
+    let second = data.peek()?;
+    if second == '+' || second == '-' {
+        data.next();
+    }
+
+    while let Some('_') = data.peek() {
+        data.next();
+    }
+
+    let (_, data) = DecLiteral::parse_token(data)?;
+    Some(data)
 }
 
-impl tokenizer_trait::Token for ExponentPart {
-    fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        let marker = data.next()?;
-        let sign;
-        if marker != 'e' && marker != 'E' {
-            return None;
-        }
-        let second = *data.peek()?;
-        if second == '+' {
-            data.next();
-            sign = Some(true);
-        } else if second == '-' {
-            data.next();
-            sign = Some(false);
-        } else {
-            sign = None;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        while let Some('_') = data.peek() {
-            data.next();
+    /// `src` lexed as a single float literal
+    fn float(src: &str) -> FloatLiteral {
+        let (literal, rest) = FloatLiteral::parse_token(SrcIterator::new(src)).unwrap();
+        assert_eq!(rest.rest(), "", "{src:?} isn't a single literal");
+        literal
+    }
+
+    /// Every form the lexer takes is one `str::parse` takes once the separators are gone
+    #[test]
+    fn forms() {
+        let literals = [
+            ("1.", 1.0),
+            ("1.5", 1.5),
+            ("1e3", 1e3),
+            ("1E+3", 1e3),
+            ("2.5e-3", 2.5e-3),
+            ("1_000.000_1", 1000.0001),
+            ("1e_3", 1e3),
+            ("1.5E-_2_", 1.5e-2),
+            ("1e400", f64::INFINITY),
+            ("0.0", 0.0),
+        ];
+        for (src, value) in literals {
+            assert_eq!(float(src).value(), value, "{src}");
         }
+    }
 
-        let exponent = DecLiteral::parse_token(data)?;
-        Some((
-            Self {
-                sign,
-                exponent: exponent.0,
-            },
-            exponent.1,
-        ))
+    #[test]
+    fn rounding() {
+        assert_eq!(float("0.1").value(), 0.1);
+        assert_eq!(float("0.1f64").value(), 0.1);
+        assert_eq!(float("0.1f32").value(), 0.1f32 as f64);
+        assert_ne!(float("0.1f32").value(), 0.1);
+        // 2^24 + 1 is the first integer an f32 can't hold, it rounds to even
+        assert_eq!(float("16777217.0").value(), 16777217.0);
+        assert_eq!(float("16777217.0f32").value(), 16777216.0);
+        assert_eq!(float("1e39f32").value(), f64::INFINITY);
+        assert_eq!(float("1e39").value(), 1e39);
+    }
+
+    #[test]
+    fn typed_suffixes() {
+        let literal = float("1.0f32");
+        assert_eq!(literal.source(), "1.0");
+        assert_eq!(literal.suffix(), Some("f32"));
+        assert_eq!(literal.float_suffix(), Some(FloatSuffix::F32));
+        assert_eq!(float("1e3f64").float_suffix(), Some(FloatSuffix::F64));
+
+        let literal = float("1.0u8");
+        assert_eq!(literal.suffix(), Some("u8"));
+        assert_eq!(literal.float_suffix(), None);
+        assert_eq!(literal.value(), 1.0);
+        assert_eq!(float("1.5").suffix(), None);
     }
 }
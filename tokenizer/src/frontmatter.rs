@@ -0,0 +1,269 @@
+use std::ops::Range;
+
+use tokenizer_trait::SrcIterator;
+
+use crate::lex_error::LexErrorKind;
+
+/// The `---` fenced block with cargo's manifest at the start of a single-file cargo script:
+///
+/// ```text
+/// ---cargo
+/// [dependencies]
+/// ---
+/// ```
+#[derive(Debug)]
+pub struct Frontmatter {
+    infostring: Option<Box<str>>,
+    body: Box<str>,
+}
+
+impl Frontmatter {
+    /// What follows the opening fence, like the `cargo` in `---cargo`
+    pub fn infostring(&self) -> Option<&str> {
+        self.infostring.as_deref()
+    }
+
+    /// Everything between the fence lines
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+}
+
+type Lexed<'a> = (
+    Result<Frontmatter, (LexErrorKind, Range<usize>)>,
+    SrcIterator<'a>,
+);
+
+/// Lexes a frontmatter block starting at the `---` at the start of `data`. Only whitespace may
+/// come before the block in the file, which is up to the caller to check. `first` is whether this
+/// is the first block, there may only be one.
+///
+/// On error the block is still skipped as a whole, up to the end of the closing fence line or,
+/// if it's never closed, the end of the file.
+pub(crate) fn lex_frontmatter(mut data: SrcIterator, first: bool) -> Lexed {
+    let start = data.offset();
+    // the first problem found is the one reported
+    let mut error = None;
+    if !at_line_start(data) {
+        error.get_or_insert((LexErrorKind::FrontmatterNotAtLineStart, start..start + 3));
+    }
+
+    data.eat_while(|chr| chr == '-');
+    let fence_len = data.offset() - start;
+    data.eat_while(|chr| chr != '\n' && chr.is_whitespace());
+    let infostring_start = data.offset();
+    data.eat_while(|chr| chr != '\n');
+    let infostring = data.slice_from(infostring_start).trim_end();
+    if !infostring.is_empty() && !is_valid_infostring(infostring) {
+        let range = infostring_start..infostring_start + infostring.len();
+        error.get_or_insert((LexErrorKind::InvalidFrontmatterInfostring, range));
+    }
+    data.eat("\n");
+
+    // the first fence line closes the block, whether it matches the opening one or not
+    let body_start = data.offset();
+    let body_end = loop {
+        let line_start = data.offset();
+        data.eat_while(|chr| chr != '\n' && chr.is_whitespace());
+        if data.starts_with("---") {
+            break line_start;
+        }
+        data.eat_while(|chr| chr != '\n');
+        if !data.eat("\n") {
+            let range = start..data.offset();
+            return (Err((LexErrorKind::UnclosedFrontmatter, range)), data);
+        }
+    };
+
+    let fence_start = data.offset();
+    if fence_start != body_end {
+        error.get_or_insert((
+            LexErrorKind::FrontmatterNotAtLineStart,
+            fence_start..fence_start + 3,
+        ));
+    }
+    data.eat_while(|chr| chr == '-');
+    if data.offset() - fence_start != fence_len {
+        let range = fence_start..data.offset();
+        error.get_or_insert((LexErrorKind::FrontmatterFenceMismatch, range));
+    }
+    data.eat_while(|chr| chr != '\n' && chr.is_whitespace());
+    let text_start = data.offset();
+    data.eat_while(|chr| chr != '\n');
+    if data.offset() != text_start {
+        let range = text_start..data.offset();
+        error.get_or_insert((LexErrorKind::TextAfterFrontmatterFence, range));
+    }
+
+    if !first {
+        return (
+            Err((LexErrorKind::MultipleFrontmatter, start..data.offset())),
+            data,
+        );
+    }
+    if let Some(error) = error {
+        return (Err(error), data);
+    }
+    let frontmatter = Frontmatter {
+        infostring: (!infostring.is_empty()).then(|| infostring.into()),
+        body: data.src()[body_start..body_end].into(),
+    };
+    (Ok(frontmatter), data)
+}
+
+fn at_line_start(data: SrcIterator) -> bool {
+    let before = &data.src()[..data.offset()];
+    // a byte order mark isn't part of the first line
+    let before = before.strip_prefix('\u{FEFF}').unwrap_or(before);
+    before.chars().next_back().is_none_or(|chr| chr == '\n')
+}
+
+/// Like an identifier, but `-` and `.` are allowed too so that `---Cargo.toml` works
+fn is_valid_infostring(infostring: &str) -> bool {
+    let mut chars = infostring.chars();
+    chars
+        .next()
+        .is_some_and(|chr| chr == '_' || unicode_ident::is_xid_start(chr))
+        && chars.all(|chr| chr == '-' || chr == '.' || unicode_ident::is_xid_continue(chr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TokenKind, TokenizeOptions, span::FileId, tokenize};
+
+    type Result = std::result::Result<(Option<String>, String), (LexErrorKind, Range<usize>)>;
+
+    /// The block at the start of `src` and where lexing it stopped
+    fn lex(src: &str) -> (Result, usize) {
+        let (frontmatter, remaining) = lex_frontmatter(SrcIterator::new(src), true);
+        let frontmatter = frontmatter.map(|frontmatter| {
+            let infostring = frontmatter.infostring().map(str::to_string);
+            (infostring, frontmatter.body().to_string())
+        });
+        (frontmatter, remaining.offset())
+    }
+
+    fn ok(infostring: Option<&str>, body: &str) -> Result {
+        Ok((infostring.map(str::to_string), body.to_string()))
+    }
+
+    #[test]
+    fn block() {
+        assert_eq!(lex("---\nx\ny\n---\nfn"), (ok(None, "x\ny\n"), 11));
+        assert_eq!(lex("---\n---"), (ok(None, ""), 7));
+    }
+
+    #[test]
+    fn infostring() {
+        assert_eq!(lex("---cargo\n---\n").0, ok(Some("cargo"), ""));
+        assert_eq!(lex("--- Cargo.toml \n---\n").0, ok(Some("Cargo.toml"), ""));
+        assert_eq!(lex("---_a-b.c\n---\n").0, ok(Some("_a-b.c"), ""));
+        assert_eq!(
+            lex("---1x\n---\n").0,
+            Err((LexErrorKind::InvalidFrontmatterInfostring, 3..5))
+        );
+        assert_eq!(
+            lex("---a b\n---\n").0,
+            Err((LexErrorKind::InvalidFrontmatterInfostring, 3..6))
+        );
+        assert_eq!(
+            lex("---.toml\n---\n").0,
+            Err((LexErrorKind::InvalidFrontmatterInfostring, 3..8))
+        );
+    }
+
+    #[test]
+    fn mismatched_fences() {
+        // the first fence line closes the block, even when it is too short
+        assert_eq!(
+            lex("----\nx\n---\nfn"),
+            (Err((LexErrorKind::FrontmatterFenceMismatch, 7..10)), 10)
+        );
+        assert_eq!(
+            lex("---\nx\n----\nfn"),
+            (Err((LexErrorKind::FrontmatterFenceMismatch, 6..10)), 10)
+        );
+    }
+
+    #[test]
+    fn unclosed() {
+        assert_eq!(
+            lex("---\nx\n"),
+            (Err((LexErrorKind::UnclosedFrontmatter, 0..6)), 6)
+        );
+        assert_eq!(
+            lex("---cargo"),
+            (Err((LexErrorKind::UnclosedFrontmatter, 0..8)), 8)
+        );
+    }
+
+    #[test]
+    fn text_after_closing_fence() {
+        assert_eq!(
+            lex("---\nx\n--- y \nfn"),
+            (Err((LexErrorKind::TextAfterFrontmatterFence, 10..12)), 12)
+        );
+    }
+
+    #[test]
+    fn indented_fence() {
+        assert_eq!(
+            lex("---\nx\n  ---\nfn"),
+            (Err((LexErrorKind::FrontmatterNotAtLineStart, 8..11)), 11)
+        );
+        let (frontmatter, _) =
+            lex_frontmatter(SrcIterator::new(" ---\n---\n").with_offset(1), true);
+        assert_eq!(
+            frontmatter.unwrap_err(),
+            (LexErrorKind::FrontmatterNotAtLineStart, 1..4)
+        );
+    }
+
+    /// Kinds of the tokens `src` lexes to, and the errors found
+    fn tokens(src: &str) -> (Vec<String>, Vec<String>) {
+        let options = TokenizeOptions::default();
+        let (tokens, errors) = match tokenize(SrcIterator::new(src), FileId::default(), &options) {
+            Ok(tokens) => (tokens, Box::default()),
+            Err(error) => (error.tokens, error.errors),
+        };
+        let tokens = tokens
+            .iter()
+            .map(|token| match &token.kind {
+                TokenKind::Frontmatter(frontmatter) => format!(
+                    "frontmatter {:?} {:?} {:?}",
+                    frontmatter.infostring(),
+                    frontmatter.body(),
+                    token.span.byte_range()
+                ),
+                kind => format!("{kind:?}"),
+            })
+            .collect();
+        (
+            tokens,
+            errors.iter().map(|error| error.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn second_block() {
+        let (tokens, errors) = tokens("---\n---\n\n---\n---\n");
+        assert_eq!(tokens[0], "frontmatter None \"\" 0..7");
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(errors, ["4:1: only one frontmatter block is allowed"]);
+    }
+
+    #[test]
+    fn after_byte_order_mark() {
+        let (tokens, errors) = tokens("\u{FEFF}---\nx\n---\n");
+        assert_eq!(tokens, ["frontmatter None \"x\\n\" 3..12"]);
+        assert_eq!(errors, Vec::<String>::new());
+    }
+
+    #[test]
+    fn crlf() {
+        let (tokens, errors) = tokens("---cargo\r\nx\r\n---\r\nfn");
+        assert_eq!(tokens[0], "frontmatter Some(\"cargo\") \"x\\n\" 0..16");
+        assert_eq!(errors, Vec::<String>::new());
+    }
+}
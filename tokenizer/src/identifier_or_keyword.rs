@@ -1,25 +1,30 @@
 use tokenizer_trait::{SrcIterator, Token};
+use unicode_normalization::UnicodeNormalization;
 
-const STRICT_KEYWORD_LIST: &[&str] = &[
-    "_", "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
-    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
-    "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true",
-    "type", "unsafe", "use", "where", "while", "async", "await", "dyn",
-];
-
-const RESERVED_KEYWORD_LIST: &[&str] = &[
-    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "typeof", "unsized",
-    "virtual", "yield", "try", "gen",
-];
+use crate::{
+    keyword::Keyword,
+    symbol::{Symbol, kw},
+};
 
 #[derive(Debug)]
 pub struct IdentifierOrKeyword {
-    parsed: String,
+    parsed: Symbol,
 }
 
 impl IdentifierOrKeyword {
-    pub fn parsed(&self) -> &str {
-        &self.parsed
+    /// The identifier in NFC, which is how rustc compares identifiers
+    pub fn parsed(&self) -> &'static str {
+        self.parsed.as_str()
+    }
+
+    pub fn symbol(&self) -> Symbol {
+        self.parsed
+    }
+
+    /// The weak keyword this identifier spells, like `union`. Strict and reserved keywords are
+    /// lexed as [`Keyword`]s, so an identifier can only be a keyword that is weak in its edition.
+    pub fn weak_keyword(&self) -> Option<Keyword> {
+        Keyword::from_symbol(self.parsed).filter(|keyword| keyword.can_be_weak())
     }
 }
 
@@ -28,22 +33,19 @@ impl Token for IdentifierOrKeyword {
     where
         Self: Sized,
     {
+        let start = data.offset();
         let first = data.next()?;
         if first != '_' && !unicode_ident::is_xid_start(first) {
             return None;
         }
-
-        let mut parsed = String::new();
-        parsed.push(first);
-        while let Some(chr) = data.peek() {
-            let is_xid_continue = unicode_ident::is_xid_continue(*chr);
-            if !is_xid_continue {
-                break;
-            }
-            parsed.push(*chr);
-            data.next();
-        }
-
+        data.eat_while(unicode_ident::is_xid_continue);
+
+        let text = data.slice_from(start);
+        let parsed = if text.is_ascii() {
+            Symbol::intern(text)
+        } else {
+            Symbol::intern(&text.nfc().collect::<String>())
+        };
         Some((Self { parsed }, data))
     }
 }
@@ -53,16 +55,27 @@ pub struct RawIdentifier {
     inner: IdentifierOrKeyword,
 }
 
+impl RawIdentifier {
+    /// The identifier without the `r#`
+    pub fn parsed(&self) -> &'static str {
+        self.inner.parsed()
+    }
+
+    pub fn symbol(&self) -> Symbol {
+        self.inner.symbol()
+    }
+}
+
 impl Token for RawIdentifier {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)>
     where
         Self: Sized,
     {
-        if data.next()? == 'r' && data.next()? == '#' {
-            let inner = IdentifierOrKeyword::parse_token(data)?;
-            return Some((Self { inner: inner.0 }, inner.1));
+        if !data.eat("r#") {
+            return None;
         }
-        None
+        let (inner, data) = IdentifierOrKeyword::parse_token(data)?;
+        Some((Self { inner }, data))
     }
 }
 
@@ -77,8 +90,8 @@ impl Token for NonKeywordIdentifier {
         Self: Sized,
     {
         let inner = IdentifierOrKeyword::parse_token(data)?;
-        if STRICT_KEYWORD_LIST.contains(&inner.0.parsed.as_str())
-            || RESERVED_KEYWORD_LIST.contains(&inner.0.parsed.as_str())
+        if Keyword::from_symbol(inner.0.symbol())
+            .is_some_and(|keyword| keyword.is_reserved_in(data.edition()))
         {
             return None;
         }
@@ -93,24 +106,20 @@ pub enum Identifier {
 }
 
 impl Identifier {
-    pub fn parsed(&self) -> &str {
+    pub fn parsed(&self) -> &'static str {
         match self {
             Self::NonKeywordIdentifier(inner) => inner.inner.parsed(),
             Self::RawIdentifier(inner) => inner.inner.parsed(),
         }
     }
 
-    pub fn from_other(other: super::Token) -> Option<Self> {
+    pub fn from_other(other: super::TokenKind) -> Option<Self> {
         match other {
-            super::Token::IdentifierOrKeyword(inner) => {
-                if STRICT_KEYWORD_LIST.contains(&inner.parsed.as_str())
-                    || RESERVED_KEYWORD_LIST.contains(&inner.parsed.as_str())
-                {
-                    return None;
-                }
+            // keywords are lexed as `TokenKind::Keyword`, so this is never one
+            super::TokenKind::IdentifierOrKeyword(inner) => {
                 Some(Self::NonKeywordIdentifier(NonKeywordIdentifier { inner }))
             }
-            super::Token::RawIdentifier(ident) => Some(Self::RawIdentifier(ident)),
+            super::TokenKind::RawIdentifier(ident) => Some(Self::RawIdentifier(ident)),
             _ => None,
         }
     }
@@ -124,18 +133,19 @@ impl Token for ReservedRawIdentifier {
     where
         Self: Sized,
     {
-        if data.next()? != 'r' || data.next()? != '#' {
+        if !data.eat("r#") {
             return None;
         }
-        const RESERVED_RAW_IDENT_TABLE: &[&str] = &["_", "crate", "self", "Self", "super"];
-        for reserved_raw_ident in RESERVED_RAW_IDENT_TABLE {
-            let zipped = reserved_raw_ident.chars().zip(data.clone());
-            if zipped.clone().all(|(a, b)| a == b) && zipped.count() == reserved_raw_ident.len() {
-                (0..reserved_raw_ident.len()).for_each(|_| {
-                    data.next();
-                });
-                return Some((Self, data));
-            }
+        const RESERVED_RAW_IDENT_TABLE: &[Symbol] = &[
+            kw::Underscore,
+            kw::Crate,
+            kw::SelfLower,
+            kw::SelfUpper,
+            kw::Super,
+        ];
+        let (ident, data) = IdentifierOrKeyword::parse_token(data)?;
+        if RESERVED_RAW_IDENT_TABLE.contains(&ident.symbol()) {
+            return Some((Self, data));
         }
         None
     }
@@ -1,285 +1,338 @@
 use tokenizer_trait::SrcIterator;
 
-use crate::suffix::SuffixNoE;
+use std::{fmt::Display, ops::Range};
+
+use crate::suffix::{FloatSuffix, IntegerSuffix, SuffixNoE};
 
 #[derive(Debug)]
 pub struct IntegerLiteral {
     kind: IntegerLiteralKind,
+    /// The literal as written, without the suffix
+    source: Box<str>,
     suffix: Option<SuffixNoE>,
 }
 
 impl tokenizer_trait::Token for IntegerLiteral {
     fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        if let Some((bin_literal, data)) = BinLiteral::parse_token(data.clone()) {
-            let suffix = SuffixNoE::parse_token(data.clone());
-            let new_iter = suffix.as_ref().map_or(data.clone(), |s| s.1.clone());
-            Some((
-                Self {
-                    kind: IntegerLiteralKind::Bin(bin_literal),
-                    suffix: suffix.map(|s| s.0),
-                },
-                new_iter,
-            ))
-        } else if let Some((oct_literal, data)) = OctLiteral::parse_token(data.clone()) {
-            let suffix = SuffixNoE::parse_token(data.clone());
-            let new_iter = suffix.as_ref().map_or(data.clone(), |s| s.1.clone());
-            Some((
-                Self {
-                    kind: IntegerLiteralKind::Oct(oct_literal),
-                    suffix: suffix.map(|s| s.0),
-                },
-                new_iter,
-            ))
-        } else if let Some((hex_literal, data)) = HexLiteral::parse_token(data.clone()) {
-            let suffix = SuffixNoE::parse_token(data.clone());
-            let new_iter = suffix.as_ref().map_or(data.clone(), |s| s.1.clone());
-            Some((
+        let start = data.offset();
+        let (kind, data) = if let Some((_, data)) = BinLiteral::parse_token(data) {
+            (IntegerLiteralKind::Bin, data)
+        } else if let Some((_, data)) = OctLiteral::parse_token(data) {
+            (IntegerLiteralKind::Oct, data)
+        } else if let Some((_, data)) = HexLiteral::parse_token(data) {
+            (IntegerLiteralKind::Hex, data)
+        } else {
+            let (_, data) = DecLiteral::parse_token(data)?;
+            (IntegerLiteralKind::Dec, data)
+        };
+        let source = data.slice_from(start).into();
+        match SuffixNoE::parse_token(data) {
+            Some((suffix, data)) => Some((
                 Self {
-                    kind: IntegerLiteralKind::Hex(hex_literal),
-                    suffix: suffix.map(|s| s.0),
+                    kind,
+                    source,
+                    suffix: Some(suffix),
                 },
-                new_iter,
-            ))
-        } else {
-            let (dec_literal, data) = DecLiteral::parse_token(data)?;
-            let suffix = SuffixNoE::parse_token(data.clone());
-            let new_iter = suffix.as_ref().map_or(data.clone(), |s| s.1.clone());
-            Some((
+                data,
+            )),
+            None => Some((
                 Self {
-                    kind: IntegerLiteralKind::Dec(dec_literal),
-                    suffix: suffix.map(|s| s.0),
+                    kind,
+                    source,
+                    suffix: None,
                 },
-                new_iter,
-            ))
+                data,
+            )),
         }
     }
 }
 
-#[derive(Debug)]
-enum IntegerLiteralKind {
-    Bin(BinLiteral),
-    Oct(OctLiteral),
-    Dec(DecLiteral),
-    Hex(HexLiteral),
+impl IntegerLiteral {
+    pub fn radix(&self) -> u32 {
+        match self.kind {
+            IntegerLiteralKind::Bin => 2,
+            IntegerLiteralKind::Oct => 8,
+            IntegerLiteralKind::Dec => 10,
+            IntegerLiteralKind::Hex => 16,
+        }
+    }
+
+    /// Digits as written, underscores included and without the radix prefix
+    pub fn digits(&self) -> &str {
+        match self.kind {
+            IntegerLiteralKind::Dec => &self.source,
+            _ => &self.source[2..],
+        }
+    }
+
+    /// Value of the literal, ignoring the suffix. Whether it fits the suffixed type is up to the
+    /// caller.
+    pub fn value(&self) -> Result<u128, Overflow> {
+        parse_digits(self.digits(), self.radix())
+    }
+
+    /// The literal as written, radix prefix included and without the suffix
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+
+    /// `None` without a suffix, and for suffixes that aren't integer types
+    pub fn integer_suffix(&self) -> Option<IntegerSuffix> {
+        IntegerSuffix::from_suffix(self.suffix()?)
+    }
+
+    /// `1f32` is a float literal even though it lexes like an integer
+    pub fn float_suffix(&self) -> Option<FloatSuffix> {
+        FloatSuffix::from_suffix(self.suffix()?)
+    }
 }
 
-#[derive(Debug)]
-enum BinDigit {
-    Zero,
-    One,
+/// The literal doesn't fit in a `u128`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "integer literal is too large")
+    }
 }
 
-#[derive(Debug)]
-enum OctDigit {
-    Zero,
-    One,
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
+impl std::error::Error for Overflow {}
+
+/// Value of `digits` in base `radix`, skipping `_` separators. `digits` has to be valid in the
+/// radix, which the lexer already made sure of.
+pub(crate) fn parse_digits(digits: &str, radix: u32) -> Result<u128, Overflow> {
+    digits
+        .chars()
+        .filter(|chr| *chr != '_')
+        .try_fold(0u128, |value, chr| {
+            let digit = chr
+                .to_digit(radix)
+                .expect("lexer only accepts digits of the radix");
+            value
+                .checked_mul(radix as u128)
+                .and_then(|value| value.checked_add(digit as u128))
+                .ok_or(Overflow)
+        })
 }
 
-#[derive(Debug)]
-enum DecDigit {
-    Zero,
-    One,
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
+#[derive(Debug, Clone, Copy)]
+enum IntegerLiteralKind {
+    Bin,
+    Oct,
+    Dec,
+    Hex,
 }
 
-#[derive(Debug)]
-pub enum HexDigit {
-    Zero,
-    One,
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    A,
-    B,
-    C,
-    D,
-    E,
-    F,
+/// Reads the digits of a literal in base `radix`, underscores included. Without a prefix the
+/// first char has to be a digit, otherwise `_1` would be a number and not an identifier.
+fn scan_digits<'a>(
+    mut data: SrcIterator<'a>,
+    radix: u32,
+    prefix: &str,
+) -> Option<(Range<usize>, SrcIterator<'a>)> {
+    if !data.eat(prefix) {
+        return None;
+    }
+    let start = data.offset();
+    if prefix.is_empty() && !data.peek()?.is_ascii_digit() {
+        return None;
+    }
+    data.eat_while(|chr| chr == '_');
+    if !data.peek()?.is_digit(radix) {
+        return None;
+    }
+    data.eat_while(|chr| chr == '_' || chr.is_digit(radix));
+    Some((start..data.offset(), data))
 }
 
 #[derive(Debug)]
 pub struct DecLiteral {
-    content: Box<[DecDigit]>,
+    /// Where the digits are in the source, without the prefix
+    digits: Range<usize>,
+}
+
+impl DecLiteral {
+    pub fn digits(&self) -> Range<usize> {
+        self.digits.clone()
+    }
 }
 
 impl tokenizer_trait::Token for DecLiteral {
-    fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        let mut content = Vec::new();
-        while let Some(chr) = data.peek() {
-            let digit = match chr {
-                '0' => DecDigit::Zero,
-                '1' => DecDigit::One,
-                '2' => DecDigit::Two,
-                '3' => DecDigit::Three,
-                '4' => DecDigit::Four,
-                '5' => DecDigit::Five,
-                '6' => DecDigit::Six,
-                '7' => DecDigit::Seven,
-                '8' => DecDigit::Eight,
-                '9' => DecDigit::Nine,
-                '_' => {
-                    data.next();
-                    continue;
-                },
-                _ => break,
-            };
-            content.push(digit);
-            data.next();
-        }
-        if content.is_empty() {
-            return None;
-        }
-        Some((
-            Self {
-                content: content.into_boxed_slice(),
-            },
-            data,
-        ))
+    fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let (digits, data) = scan_digits(data, 10, "")?;
+        Some((Self { digits }, data))
     }
 }
 
 #[derive(Debug)]
 pub struct BinLiteral {
-    content: Box<[BinDigit]>,
+    /// Where the digits are in the source, without the prefix
+    digits: Range<usize>,
+}
+
+impl BinLiteral {
+    pub fn digits(&self) -> Range<usize> {
+        self.digits.clone()
+    }
 }
 
 impl tokenizer_trait::Token for BinLiteral {
-    fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        if data.next()? != '0' || data.next()? != 'b' {
-            return None;
-        }
-        let mut content = Vec::new();
-        while let Some(chr) = data.peek() {
-            let digit = match chr {
-                '0' => BinDigit::Zero,
-                '1' => BinDigit::One,
-                '_' => {
-                    data.next();
-                    continue;
-                },
-                _ => break,
-            };
-            content.push(digit);
-            data.next();
-        }
-        if content.is_empty() {
-            return None;
-        }
-        Some((
-            Self {
-                content: content.into_boxed_slice(),
-            },
-            data,
-        ))
+    fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let (digits, data) = scan_digits(data, 2, "0b")?;
+        Some((Self { digits }, data))
     }
 }
 
 #[derive(Debug)]
 pub struct OctLiteral {
-    content: Box<[OctDigit]>,
+    /// Where the digits are in the source, without the prefix
+    digits: Range<usize>,
+}
+
+impl OctLiteral {
+    pub fn digits(&self) -> Range<usize> {
+        self.digits.clone()
+    }
 }
 
 impl tokenizer_trait::Token for OctLiteral {
-    fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        if data.next()? != '0' || data.next()? != 'o' {
-            return None;
-        }
-        let mut content = Vec::new();
-        while let Some(chr) = data.peek() {
-            let digit = match chr {
-                '0' => OctDigit::Zero,
-                '1' => OctDigit::One,
-                '2' => OctDigit::Two,
-                '3' => OctDigit::Three,
-                '4' => OctDigit::Four,
-                '5' => OctDigit::Five,
-                '6' => OctDigit::Six,
-                '7' => OctDigit::Seven,
-                '_' => {
-                    data.next();
-                    continue;
-                },
-                _ => break,
-            };
-            content.push(digit);
-            data.next();
-        }
-        if content.is_empty() {
-            return None;
-        }
-        Some((
-            Self {
-                content: content.into_boxed_slice(),
-            },
-            data,
-        ))
+    fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let (digits, data) = scan_digits(data, 8, "0o")?;
+        Some((Self { digits }, data))
     }
 }
 
 #[derive(Debug)]
 pub struct HexLiteral {
-    content: Box<[HexDigit]>,
+    /// Where the digits are in the source, without the prefix
+    digits: Range<usize>,
+}
+
+impl HexLiteral {
+    pub fn digits(&self) -> Range<usize> {
+        self.digits.clone()
+    }
 }
 
 impl tokenizer_trait::Token for HexLiteral {
-    fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        if data.next()? != '0' || data.next()? != 'x' {
-            return None;
+    fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let (digits, data) = scan_digits(data, 16, "0x")?;
+        Some((Self { digits }, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokenizer_trait::Token;
+
+    use super::*;
+
+    /// `src` lexed as a single integer literal
+    fn integer(src: &str) -> IntegerLiteral {
+        let (literal, rest) = IntegerLiteral::parse_token(SrcIterator::new(src)).unwrap();
+        assert_eq!(rest.rest(), "", "{src:?} isn't a single literal");
+        literal
+    }
+
+    #[test]
+    fn radixes() {
+        let literals = [
+            ("0b1010_1010", 2, "1010_1010", 0b1010_1010),
+            ("0o7_7", 8, "7_7", 0o77),
+            ("1_000", 10, "1_000", 1000),
+            ("0xdead_BEEF", 16, "dead_BEEF", 0xdead_beef),
+            ("0x__1_", 16, "__1_", 1),
+            ("0___", 10, "0___", 0),
+        ];
+        for (src, radix, digits, value) in literals {
+            let literal = integer(src);
+            assert_eq!(literal.radix(), radix, "{src}");
+            assert_eq!(literal.digits(), digits, "{src}");
+            assert_eq!(literal.source(), src);
+            assert_eq!(literal.value(), Ok(value), "{src}");
         }
-        let mut content = Vec::new();
-        while let Some(chr) = data.peek() {
-            let digit = match chr {
-                '0' => HexDigit::Zero,
-                '1' => HexDigit::One,
-                '2' => HexDigit::Two,
-                '3' => HexDigit::Three,
-                '4' => HexDigit::Four,
-                '5' => HexDigit::Five,
-                '6' => HexDigit::Six,
-                '7' => HexDigit::Seven,
-                '8' => HexDigit::Eight,
-                '9' => HexDigit::Nine,
-                'a' | 'A' => HexDigit::A,
-                'b' | 'B' => HexDigit::B,
-                'c' | 'C' => HexDigit::C,
-                'd' | 'D' => HexDigit::D,
-                'e' | 'E' => HexDigit::E,
-                'f' | 'F' => HexDigit::F,
-                '_' => {
-                    data.next();
-                    continue;
-                },
-                _ => break,
-            };
-            content.push(digit);
-            data.next();
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(integer(&u128::MAX.to_string()).value(), Ok(u128::MAX));
+        assert_eq!(
+            integer("340282366920938463463374607431768211456").value(),
+            Err(Overflow)
+        );
+        assert_eq!(
+            integer(&format!("0x{:x}", u128::MAX)).value(),
+            Ok(u128::MAX)
+        );
+        assert_eq!(
+            integer(&format!("0b1{}", "0".repeat(128))).value(),
+            Err(Overflow)
+        );
+    }
+
+    /// Whatever follows a number, it only takes digits of its radix, so `value` never sees a char
+    /// it can't parse
+    #[test]
+    fn only_digits_of_the_radix() {
+        for prefix in ["", "0b", "0o", "0x"] {
+            for chr in (0..0x80u8).map(char::from) {
+                let src = format!("{prefix}1_{chr}");
+                let (literal, _) = IntegerLiteral::parse_token(SrcIterator::new(&src)).unwrap();
+                let radix = literal.radix();
+                assert!(
+                    literal
+                        .digits()
+                        .chars()
+                        .all(|digit| digit == '_' || digit.is_digit(radix)),
+                    "{src:?}"
+                );
+                assert!(literal.value().is_ok());
+            }
         }
-        if content.is_empty() {
-            return None;
+    }
+
+    #[test]
+    fn typed_suffixes() {
+        let suffixes = [
+            ("u8", IntegerSuffix::U8),
+            ("u16", IntegerSuffix::U16),
+            ("u32", IntegerSuffix::U32),
+            ("u64", IntegerSuffix::U64),
+            ("u128", IntegerSuffix::U128),
+            ("usize", IntegerSuffix::Usize),
+            ("i8", IntegerSuffix::I8),
+            ("i16", IntegerSuffix::I16),
+            ("i32", IntegerSuffix::I32),
+            ("i64", IntegerSuffix::I64),
+            ("i128", IntegerSuffix::I128),
+            ("isize", IntegerSuffix::Isize),
+        ];
+        for (suffix, integer_suffix) in suffixes {
+            let literal = integer(&format!("0o1{suffix}"));
+            assert_eq!(literal.suffix(), Some(suffix));
+            assert_eq!(literal.integer_suffix(), Some(integer_suffix));
+            assert_eq!(literal.float_suffix(), None);
+            assert_eq!(integer_suffix.is_signed(), suffix.starts_with('i'));
         }
-        Some((
-            Self {
-                content: content.into_boxed_slice(),
-            },
-            data,
-        ))
+
+        let literal = integer("1f32");
+        assert_eq!(literal.integer_suffix(), None);
+        assert_eq!(literal.float_suffix(), Some(FloatSuffix::F32));
+        assert_eq!(integer("1_f64").float_suffix(), Some(FloatSuffix::F64));
+
+        let literal = integer("1u7");
+        assert_eq!(literal.suffix(), Some("u7"));
+        assert_eq!(literal.integer_suffix(), None);
+        assert_eq!(literal.float_suffix(), None);
+
+        // the suffix would be hex digits
+        let literal = integer("0x1f32");
+        assert_eq!(literal.suffix(), None);
+        assert_eq!(literal.value(), Ok(0x1f32));
     }
 }
@@ -0,0 +1,233 @@
+use tokenizer_trait::{Edition, SrcIterator, Token};
+
+use crate::{
+    IdentifierOrKeyword,
+    symbol::{Symbol, kw},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordClass {
+    /// Can't be used as an identifier, like `fn`
+    Strict,
+    /// Not used by the language yet, but can't be used as an identifier either, like `abstract`
+    Reserved,
+    /// Only a keyword in certain places and an identifier everywhere else, like `union`
+    Weak,
+}
+
+macro_rules! keywords {
+    ($($name:ident,)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Keyword {
+            $($name,)*
+        }
+
+        impl Keyword {
+            pub fn symbol(self) -> Symbol {
+                match self {
+                    $(Self::$name => kw::$name,)*
+                }
+            }
+
+            /// Keyword spelled `symbol`, whatever its class is in any edition
+            pub fn from_symbol(symbol: Symbol) -> Option<Self> {
+                match symbol {
+                    $(kw::$name => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+keywords! {
+    Underscore,
+    As,
+    Async,
+    Await,
+    Break,
+    Const,
+    Continue,
+    Crate,
+    Dyn,
+    Else,
+    Enum,
+    Extern,
+    False,
+    Fn,
+    For,
+    If,
+    Impl,
+    In,
+    Let,
+    Loop,
+    Match,
+    Mod,
+    Move,
+    Mut,
+    Pub,
+    Ref,
+    Return,
+    SelfLower,
+    SelfUpper,
+    Static,
+    Struct,
+    Super,
+    Trait,
+    True,
+    Type,
+    Unsafe,
+    Use,
+    Where,
+    While,
+
+    Abstract,
+    Become,
+    Box,
+    Do,
+    Final,
+    Gen,
+    Macro,
+    Override,
+    Priv,
+    Try,
+    Typeof,
+    Unsized,
+    Virtual,
+    Yield,
+
+    MacroRules,
+    Raw,
+    Safe,
+    StaticLifetime,
+    Union,
+}
+
+impl Keyword {
+    pub fn as_str(self) -> &'static str {
+        self.symbol().as_str()
+    }
+
+    /// What kind of keyword this is in `edition`. `None` if it is a plain identifier there, like
+    /// `async` in 2015.
+    pub fn class(self, edition: Edition) -> Option<KeywordClass> {
+        match self {
+            Self::Async | Self::Await => {
+                (edition >= Edition::Edition2018).then_some(KeywordClass::Strict)
+            }
+            Self::Dyn if edition >= Edition::Edition2018 => Some(KeywordClass::Strict),
+            Self::Dyn => Some(KeywordClass::Weak),
+            Self::Try => (edition >= Edition::Edition2018).then_some(KeywordClass::Reserved),
+            Self::Gen => (edition >= Edition::Edition2024).then_some(KeywordClass::Reserved),
+            Self::Abstract
+            | Self::Become
+            | Self::Box
+            | Self::Do
+            | Self::Final
+            | Self::Macro
+            | Self::Override
+            | Self::Priv
+            | Self::Typeof
+            | Self::Unsized
+            | Self::Virtual
+            | Self::Yield => Some(KeywordClass::Reserved),
+            Self::MacroRules | Self::Raw | Self::Safe | Self::StaticLifetime | Self::Union => {
+                Some(KeywordClass::Weak)
+            }
+            _ => Some(KeywordClass::Strict),
+        }
+    }
+
+    /// Strict or reserved in `edition`, so it can't be an identifier
+    pub fn is_reserved_in(self, edition: Edition) -> bool {
+        matches!(
+            self.class(edition),
+            Some(KeywordClass::Strict | KeywordClass::Reserved)
+        )
+    }
+
+    /// Weak in at least one edition
+    pub fn can_be_weak(self) -> bool {
+        Edition::ALL
+            .iter()
+            .any(|edition| self.class(*edition) == Some(KeywordClass::Weak))
+    }
+}
+
+/// Only strict and reserved keywords are lexed as keywords. Weak keywords are lexed as identifiers,
+/// see [`IdentifierOrKeyword::weak_keyword`].
+impl Token for Keyword {
+    fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let (ident, data) = IdentifierOrKeyword::parse_token(data)?;
+        let keyword = Self::from_symbol(ident.symbol())?;
+        if !keyword.is_reserved_in(data.edition()) {
+            return None;
+        }
+        Some((keyword, data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Edition::*;
+    use KeywordClass::*;
+
+    #[test]
+    fn class_per_edition() {
+        let (strict, reserved, weak) = (Some(Strict), Some(Reserved), Some(Weak));
+        let classes = [
+            (Keyword::Async, [None, strict, strict, strict]),
+            (Keyword::Await, [None, strict, strict, strict]),
+            (Keyword::Dyn, [weak, strict, strict, strict]),
+            (Keyword::Try, [None, reserved, reserved, reserved]),
+            (Keyword::Gen, [None, None, None, reserved]),
+            (Keyword::Fn, [strict; 4]),
+            (Keyword::SelfUpper, [strict; 4]),
+            (Keyword::Abstract, [reserved; 4]),
+            (Keyword::Yield, [reserved; 4]),
+            (Keyword::Union, [weak; 4]),
+            (Keyword::MacroRules, [weak; 4]),
+            (Keyword::StaticLifetime, [weak; 4]),
+            (Keyword::Safe, [weak; 4]),
+            (Keyword::Raw, [weak; 4]),
+        ];
+        let editions = [Edition2015, Edition2018, Edition2021, Edition2024];
+        for (keyword, expected) in classes {
+            for (edition, class) in editions.into_iter().zip(expected) {
+                assert_eq!(keyword.class(edition), class, "{keyword:?} in {edition:?}");
+                assert_eq!(
+                    keyword.is_reserved_in(edition),
+                    matches!(class, Some(Strict | Reserved)),
+                    "{keyword:?} in {edition:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn can_be_weak() {
+        assert!(Keyword::Dyn.can_be_weak());
+        assert!(Keyword::Union.can_be_weak());
+        assert!(!Keyword::Async.can_be_weak());
+        assert!(!Keyword::Gen.can_be_weak());
+        assert!(!Keyword::Fn.can_be_weak());
+    }
+
+    #[test]
+    fn spelling() {
+        let spellings = [
+            (Keyword::Underscore, "_"),
+            (Keyword::SelfLower, "self"),
+            (Keyword::SelfUpper, "Self"),
+            (Keyword::MacroRules, "macro_rules"),
+            (Keyword::StaticLifetime, "'static"),
+            (Keyword::Gen, "gen"),
+        ];
+        for (keyword, text) in spellings {
+            assert_eq!(keyword.as_str(), text);
+            assert_eq!(Keyword::from_symbol(Symbol::intern(text)), Some(keyword));
+        }
+        assert_eq!(Keyword::from_symbol(Symbol::intern("foo")), None);
+    }
+}
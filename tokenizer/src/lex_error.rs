@@ -0,0 +1,427 @@
+use std::{fmt::Display, ops::Range};
+
+use tokenizer_trait::{Edition, SrcIterator, Token};
+
+use crate::{
+    Token as SpannedToken, TokenKind, comment,
+    punctuation::Punctuation,
+    reserved_token::ReservedToken,
+    span::Span,
+    string_escapes::{ByteEscape, UnicodeEscape},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    UnterminatedBlockComment,
+    /// Doc comments may only contain CR as part of a CRLF
+    BareCrInDocComment,
+    /// Strings have to write a CR that isn't part of a CRLF as `\r`
+    BareCrInString,
+    BareCrInRawString,
+    /// A CR in a char or byte literal has to be written as `'\r'`
+    BareCrInCharLiteral,
+    UnterminatedString,
+    UnterminatedRawString,
+    UnterminatedCharLiteral,
+    InvalidEscape,
+    /// C strings can't contain NUL, neither written out nor as an escape
+    NulInCString,
+    /// The literal is terminated but its contents are not allowed, e.g. a non-ascii char in a
+    /// byte string
+    InvalidLiteral,
+    /// A suffix that isn't a numeric type on an integer literal, like `1u7`
+    InvalidIntegerSuffix,
+    /// A suffix other than `f32` and `f64` on a float literal, like `1.0u8`
+    InvalidFloatSuffix,
+    /// A float suffix on a binary or octal literal, like `0b1f32`. Holds the radix.
+    NonDecimalFloatLiteral(u32),
+    /// Any suffix on a string, byte or char literal, like `"x"suffix`. Holds what kind of literal
+    /// it is, like `"byte string"`.
+    InvalidLiteralSuffix(&'static str),
+    /// A float literal after a field access `.` with an exponent, like `x.1e3`
+    ExponentInTupleIndex,
+    /// A suffix on a tuple index, like `x.0.1f32`
+    SuffixOnTupleIndex,
+    /// `ident"`, `ident'`, `ident#` and `'ident#`
+    ReservedPrefix,
+    ReservedNumber,
+    ReservedPounds,
+    ReservedGuardedString,
+    ReservedRawIdentifier,
+    ReservedRawLifetime,
+    UnclosedFrontmatter,
+    /// The closing fence of a frontmatter block has a different number of dashes than the opening
+    FrontmatterFenceMismatch,
+    /// Frontmatter fences can't be indented
+    FrontmatterNotAtLineStart,
+    InvalidFrontmatterInfostring,
+    TextAfterFrontmatterFence,
+    MultipleFrontmatter,
+    StrayCharacter(char),
+}
+
+impl Display for LexErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnterminatedBlockComment => write!(f, "unterminated block comment"),
+            Self::BareCrInDocComment => write!(f, "bare CR not allowed in doc comment"),
+            Self::BareCrInString => write!(f, "bare CR not allowed in string, use `\\r` instead"),
+            Self::BareCrInRawString => write!(f, "bare CR not allowed in raw string"),
+            Self::BareCrInCharLiteral => {
+                write!(
+                    f,
+                    "character literal can't contain a bare CR, use `\\r` instead"
+                )
+            }
+            Self::UnterminatedString => write!(f, "unterminated string literal"),
+            Self::UnterminatedRawString => write!(f, "unterminated raw string literal"),
+            Self::UnterminatedCharLiteral => write!(f, "unterminated character literal"),
+            Self::InvalidEscape => write!(f, "invalid escape sequence"),
+            Self::NulInCString => write!(f, "NUL character in C string literal"),
+            Self::InvalidLiteral => write!(f, "invalid literal"),
+            Self::InvalidIntegerSuffix => {
+                write!(
+                    f,
+                    "invalid suffix for number literal, expected a type like `u32` or `f64`"
+                )
+            }
+            Self::InvalidFloatSuffix => {
+                write!(
+                    f,
+                    "invalid suffix for float literal, expected `f32` or `f64`"
+                )
+            }
+            Self::NonDecimalFloatLiteral(radix) => {
+                let base = match radix {
+                    2 => "binary",
+                    8 => "octal",
+                    _ => "hexadecimal",
+                };
+                write!(f, "{} float literal is not supported", base)
+            }
+            Self::InvalidLiteralSuffix(literal) => {
+                write!(f, "suffixes on {} literals are invalid", literal)
+            }
+            Self::ExponentInTupleIndex => {
+                write!(f, "unexpected token, a tuple index can't have an exponent")
+            }
+            Self::SuffixOnTupleIndex => write!(f, "suffixes on a tuple index are invalid"),
+            Self::ReservedPrefix => write!(f, "reserved prefix"),
+            Self::ReservedNumber => write!(f, "reserved number form"),
+            Self::ReservedPounds => write!(f, "reserved multi-hash token"),
+            Self::ReservedGuardedString => write!(f, "reserved guarded string literal"),
+            Self::ReservedRawIdentifier => write!(f, "identifier cannot be a raw identifier"),
+            Self::ReservedRawLifetime => write!(f, "lifetime cannot be a raw lifetime"),
+            Self::UnclosedFrontmatter => write!(f, "unclosed frontmatter"),
+            Self::FrontmatterFenceMismatch => {
+                write!(
+                    f,
+                    "frontmatter closing fence doesn't match the opening fence"
+                )
+            }
+            Self::FrontmatterNotAtLineStart => {
+                write!(f, "frontmatter fence must be at the start of a line")
+            }
+            Self::InvalidFrontmatterInfostring => write!(f, "invalid frontmatter infostring"),
+            Self::TextAfterFrontmatterFence => {
+                write!(f, "unexpected text after frontmatter closing fence")
+            }
+            Self::MultipleFrontmatter => write!(f, "only one frontmatter block is allowed"),
+            Self::StrayCharacter(chr) => write!(f, "unknown start of token: {:?}", chr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+}
+
+impl Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.span, self.kind)
+    }
+}
+
+impl std::error::Error for LexError {}
+
+/// Returned when a source had lex errors. `tokens` still holds the whole stream, with a
+/// [`crate::TokenKind::Error`] token in place of every bad token.
+#[derive(Debug)]
+pub struct TokenizeError {
+    pub tokens: Box<[SpannedToken]>,
+    pub errors: Box<[LexError]>,
+}
+
+impl Display for TokenizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TokenizeError {}
+
+#[derive(Debug)]
+pub enum TokenizeFileError {
+    Io(std::io::Error),
+    Lex(TokenizeError),
+}
+
+impl Display for TokenizeFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Lex(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for TokenizeFileError {}
+
+impl From<std::io::Error> for TokenizeFileError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<TokenizeError> for TokenizeFileError {
+    fn from(value: TokenizeError) -> Self {
+        Self::Lex(value)
+    }
+}
+
+impl From<&ReservedToken> for LexErrorKind {
+    fn from(value: &ReservedToken) -> Self {
+        match value {
+            ReservedToken::ReservedGuardedStringLiteral(_) => Self::ReservedGuardedString,
+            ReservedToken::ReservedNumber(_) => Self::ReservedNumber,
+            ReservedToken::ReservedPounds(_) => Self::ReservedPounds,
+            ReservedToken::ReservedRawIdentifier(_) => Self::ReservedRawIdentifier,
+            ReservedToken::ReservedRawLifetime(_) => Self::ReservedRawLifetime,
+            ReservedToken::ReservedTokenDoubleQuote(_)
+            | ReservedToken::ReservedTokenLifetime(_)
+            | ReservedToken::ReservedTokenPound(_)
+            | ReservedToken::ReservedTokenSingleQuote(_) => Self::ReservedPrefix,
+        }
+    }
+}
+
+/// Whether a token that did parse is really the start of a malformed one, like the `/` of an
+/// unterminated block comment or the `b` of a bad byte string. `rest` is what follows the token.
+pub(crate) fn is_truncated(kind: &TokenKind, rest: SrcIterator) -> bool {
+    let c_strings = rest.edition() >= Edition::Edition2021;
+    let rest = rest.rest();
+    match kind {
+        TokenKind::Punctuation(Punctuation::Slash) => rest.starts_with(['*', '/']),
+        TokenKind::IdentifierOrKeyword(ident) => match ident.parsed() {
+            "b" => rest.starts_with('"') || rest.starts_with('\''),
+            "c" if c_strings => rest.starts_with('"'),
+            "cr" if c_strings => rest.trim_start_matches('#').starts_with('"'),
+            "r" | "br" => rest.trim_start_matches('#').starts_with('"'),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Called when no token parses at `data`. Figures out what went wrong and skips past the bad
+/// token so lexing can continue. Returns the error, the byte range to report it at (the whole
+/// bad token unless something more precise is known) and the cursor after the bad token.
+pub(crate) fn recover(data: SrcIterator) -> (LexErrorKind, Range<usize>, SrcIterator) {
+    let start = data.offset();
+
+    let (kind, error_range, data) = if data.starts_with("/*") || data.starts_with("//") {
+        recover_comment(data)
+    } else if let Some(res) = recover_quoted(data) {
+        res
+    } else {
+        let mut data = data;
+        let chr = data.next().unwrap();
+        (LexErrorKind::StrayCharacter(chr), None, data)
+    };
+
+    (kind, error_range.unwrap_or(start..data.offset()), data)
+}
+
+type Recovered<'a> = (LexErrorKind, Option<Range<usize>>, SrcIterator<'a>);
+
+fn recover_comment(data: SrcIterator) -> Recovered {
+    let (text, after) = if data.starts_with("//") {
+        let mut after = data;
+        after.bump("//".len());
+        let start = after.offset();
+        after.eat_while(|chr| chr != '\n');
+        (after.slice_from(start), after)
+    } else if let Some(comment) = comment::scan_block_comment(data) {
+        comment
+    } else {
+        let end = data.src().len();
+        return (
+            LexErrorKind::UnterminatedBlockComment,
+            None,
+            data.with_offset(end),
+        );
+    };
+    // otherwise the comment would have lexed, so it must be a doc comment with a bare CR in it
+    let cr = data.offset() + "//".len() + comment::find_bare_cr(text).unwrap_or(0);
+    (LexErrorKind::BareCrInDocComment, Some(cr..cr + 1), after)
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum QuotedKind {
+    Str,
+    Byte,
+    C,
+}
+
+fn recover_quoted(mut data: SrcIterator) -> Option<Recovered> {
+    let rest = data.rest();
+    let (prefix_len, kind, raw) = [
+        ("br", QuotedKind::Byte, true),
+        ("cr", QuotedKind::C, true),
+        ("r", QuotedKind::Str, true),
+        ("b", QuotedKind::Byte, false),
+        ("c", QuotedKind::C, false),
+        ("", QuotedKind::Str, false),
+    ]
+    .into_iter()
+    .find_map(|(prefix, kind, raw)| {
+        let after = rest.strip_prefix(prefix)?;
+        let after = if raw {
+            after.trim_start_matches('#')
+        } else {
+            after
+        };
+        if after.starts_with('"') || (!raw && kind != QuotedKind::C && after.starts_with('\'')) {
+            Some((prefix.len(), kind, raw))
+        } else {
+            None
+        }
+    })?;
+    data.bump(prefix_len);
+
+    if raw {
+        let hashes_start = data.offset();
+        data.eat_while(|chr| chr == '#');
+        let hashes = data.slice_from(hashes_start);
+        data.next(); // opening quote
+        // only the first problem is reported
+        let mut invalid = None;
+        loop {
+            let chr_start = data.offset();
+            let Some(chr) = data.next() else {
+                return Some((LexErrorKind::UnterminatedRawString, None, data));
+            };
+            if chr == '"' && data.eat(hashes) {
+                break;
+            }
+            if chr == '\r' {
+                invalid.get_or_insert((LexErrorKind::BareCrInRawString, chr_start..data.offset()));
+            }
+            if kind == QuotedKind::C && chr == '\0' {
+                invalid.get_or_insert((LexErrorKind::NulInCString, chr_start..data.offset()));
+            }
+        }
+        let data = skip_suffix(data);
+        return match invalid {
+            Some((kind, range)) => Some((kind, Some(range), data)),
+            None => Some((LexErrorKind::InvalidLiteral, None, data)),
+        };
+    }
+
+    let quote = data.next()?;
+    // only the first problem is reported
+    let mut invalid: Option<(LexErrorKind, Range<usize>)> = None;
+    let mut report = |kind, range| {
+        invalid.get_or_insert((kind, range));
+    };
+    loop {
+        let Some(chr) = data.peek() else {
+            let kind = if quote == '"' {
+                LexErrorKind::UnterminatedString
+            } else {
+                LexErrorKind::UnterminatedCharLiteral
+            };
+            return Some((kind, None, data));
+        };
+        if quote == '\'' && chr == '\n' {
+            return Some((LexErrorKind::UnterminatedCharLiteral, None, data));
+        }
+        if chr == quote {
+            data.next();
+            break;
+        }
+        if chr != '\\' {
+            let chr_start = data.offset();
+            data.next();
+            if kind == QuotedKind::C && chr == '\0' {
+                report(LexErrorKind::NulInCString, chr_start..data.offset());
+            }
+            if chr == '\r' {
+                let kind = match quote {
+                    '"' => LexErrorKind::BareCrInString,
+                    _ => LexErrorKind::BareCrInCharLiteral,
+                };
+                report(kind, chr_start..data.offset());
+            }
+            continue;
+        }
+
+        let escape_start = data.offset();
+        if let Some((escape, after)) = ByteEscape::parse_token(data) {
+            match kind {
+                QuotedKind::Str if escape.value() >= 0x80 => {
+                    report(LexErrorKind::InvalidEscape, escape_start..after.offset())
+                }
+                QuotedKind::C if escape.value() == 0 => {
+                    report(LexErrorKind::NulInCString, escape_start..after.offset())
+                }
+                _ => {}
+            }
+            data = after;
+            continue;
+        }
+        if kind != QuotedKind::Byte
+            && let Some((escape, after)) = UnicodeEscape::parse_token(data)
+        {
+            match escape.to_char() {
+                None => report(LexErrorKind::InvalidEscape, escape_start..after.offset()),
+                Some('\0') if kind == QuotedKind::C => {
+                    report(LexErrorKind::NulInCString, escape_start..after.offset())
+                }
+                _ => {}
+            }
+            data = after;
+            continue;
+        }
+        data.next();
+        match data.peek() {
+            Some('\n') if quote == '"' => {
+                data.next();
+            }
+            Some(_) => {
+                data.next();
+                report(LexErrorKind::InvalidEscape, escape_start..data.offset());
+            }
+            None => {}
+        }
+    }
+
+    let data = skip_suffix(data);
+    match invalid {
+        Some((kind, range)) => Some((kind, Some(range), data)),
+        None => Some((LexErrorKind::InvalidLiteral, None, data)),
+    }
+}
+
+fn skip_suffix(mut data: SrcIterator) -> SrcIterator {
+    data.eat_while(unicode_ident::is_xid_continue);
+    data
+}
@@ -4,35 +4,61 @@ pub mod byte_literal;
 pub mod byte_string_literal;
 pub mod c_string_literal;
 pub mod char_literal;
+pub mod comment;
+pub mod dump;
 pub mod float_literal;
+pub mod frontmatter;
 pub mod identifier_or_keyword;
 pub mod integer_literal;
+pub mod keyword;
+pub mod lex_error;
 pub mod lifetime_token;
+pub mod line_endings;
+#[cfg(feature = "proc-macro2")]
+pub mod proc_macro_conversion;
 pub mod punctuation;
 pub mod raw_byte_string_literal;
 pub mod raw_c_string_literal;
 pub mod raw_identifier;
 pub mod raw_string_literal;
+pub mod relex;
 pub mod reserved_token;
+pub mod span;
 pub mod string_escapes;
 pub mod string_literal;
 pub mod suffix;
+pub mod symbol;
+pub mod token_tree;
+pub mod trivia;
+pub mod unicode_lints;
 
-use std::path::Path;
+use std::{ops::Range, path::Path};
+
+pub use tokenizer_trait::{Edition, SrcIterator};
 
 use tokenizer_macro::ParseEnumToken;
-use tokenizer_trait::{SrcIterator, Token as TokenTrait};
+use tokenizer_trait::Token as TokenTrait;
+
+use crate::{
+    lex_error::{LexError, LexErrorKind, TokenizeError, TokenizeFileError},
+    line_endings::NormalizedSrc,
+    span::{FileId, Pos, PosTracker, Span},
+    trivia::{LosslessText, Trivia, TriviaKind},
+};
 
 use crate::{
     byte_literal::ByteLiteral,
     byte_string_literal::ByteStringLiteral,
     c_string_literal::CStringLiteral,
     char_literal::CharLiteral,
+    comment::{Comment, DocComment},
     float_literal::FloatLiteral,
+    frontmatter::Frontmatter,
     identifier_or_keyword::{IdentifierOrKeyword, RawIdentifier},
     integer_literal::IntegerLiteral,
+    keyword::Keyword,
     lifetime_token::LifetimeToken,
-    punctuation::Punctuation,
+    punctuation::{Punctuation, Spacing},
     raw_byte_string_literal::RawByteStringLiteral,
     raw_c_string_literal::RawCStringLiteral,
     raw_string_literal::RawStringLiteral,
@@ -40,66 +66,527 @@ use crate::{
     string_literal::StringLiteral,
 };
 
-pub fn tokenize_file(filename: &Path) -> Box<[Token]> {
-    let data = std::fs::read_to_string(filename).expect("Failed to read file");
-    let mut data = &data[..];
-    if data.starts_with('\u{FEFF}') {
-        data = &data[3..];
-    }
-    if data.starts_with("#!") {
-        let mut internal_data = data[3..].lines().next().unwrap_or("");
-        internal_data = internal_data.trim();
-        if !internal_data.starts_with('[') {
-            let first_newline = data.find('\n').unwrap_or(data.len());
-            data = &data[first_newline..];
+#[derive(Debug, Clone, Default)]
+pub struct TokenizeOptions {
+    /// Keep ordinary (non-doc) comments in the stream as [`TokenKind::Comment`] tokens. Doc
+    /// comments are always kept.
+    pub keep_comments: bool,
+    /// Keep all whitespace and comments as [`trivia::Trivia`] attached to the tokens, so that
+    /// [`trivia::print_lossless`] gives back the exact input. Ordinary comments always go into
+    /// the trivia in this mode, and the stream ends with a [`TokenKind::EndOfFile`] token.
+    pub lossless: bool,
+    /// Decides which words are keywords and which token forms are reserved
+    pub edition: Edition,
+    /// Lex the way a proc macro gets to see tokens, where a literal can have any suffix.
+    /// Otherwise suffixes that rustc rejects, like the ones in `1u7` or `"x"suffix`, are errors.
+    pub proc_macro: bool,
+}
+
+pub fn tokenize_file(
+    filename: &Path,
+    options: &TokenizeOptions,
+) -> Result<Box<[Token]>, TokenizeFileError> {
+    let data = std::fs::read_to_string(filename)?;
+    let iter = SrcIterator::new(&data);
+
+    Ok(tokenize(iter, FileId::register(filename), options)?)
+}
+
+/// Tokenizes the whole input. Lexing continues past bad tokens, so on failure the error still
+/// carries the full token stream alongside every error found. If `data` is at the start of its
+/// source, a byte order mark is skipped and a shebang line is lexed as [`TokenKind::Shebang`].
+///
+/// CRLF line endings are lexed as plain LFs, like rustc does, but spans and lossless text still
+/// refer to the original source.
+pub fn tokenize(
+    data: SrcIterator,
+    file: FileId,
+    options: &TokenizeOptions,
+) -> Result<Box<[Token]>, TokenizeError> {
+    let normalized = NormalizedSrc::new(data.src());
+    let mut lexer = Lexer::new(&normalized, data.src(), data.offset(), file, options);
+    while lexer.at_token().is_some() {
+        if let Some(token) = lexer.lex() {
+            lexer.push(token);
         }
     }
+    let (tokens, errors) = lexer.finish();
 
-    tokenize(data.chars().peekable(), filename)
+    if errors.is_empty() {
+        Ok(tokens.into_boxed_slice())
+    } else {
+        Err(TokenizeError {
+            tokens: tokens.into_boxed_slice(),
+            errors: errors.into_boxed_slice(),
+        })
+    }
 }
 
-pub fn tokenize(mut data: SrcIterator, filename: &Path) -> Box<[Token]> {
-    let mut tokens = Vec::new();
-    loop {
-        while let Some(chr) = data.peek() {
-            if chr.is_whitespace() {
-                data.next();
+/// Everything [`tokenize`] keeps track of between tokens. Besides starting at the top of a file,
+/// it can pick up behind tokens lexed earlier, which is how [`relex::relex`] only lexes the part
+/// of a file around an edit.
+pub(crate) struct Lexer<'a> {
+    /// Offsets are into the normalized text, spans into the original one
+    data: SrcIterator<'a>,
+    normalized: &'a NormalizedSrc<'a>,
+    original: &'a str,
+    positions: PosTracker<'a>,
+    file: FileId,
+    options: &'a TokenizeOptions,
+    tokens: Vec<Token>,
+    errors: Vec<LexError>,
+    /// Whitespace and comments since the last token, only collected in lossless mode
+    trivia: Vec<Trivia>,
+    /// The shebang can only be at the very start of the file
+    shebang_start: Option<usize>,
+    /// Frontmatter may only come before any other token
+    frontmatter_allowed: bool,
+    seen_frontmatter: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Starts lexing `original` at `offset`, `normalized` being that same text
+    pub(crate) fn new(
+        normalized: &'a NormalizedSrc<'a>,
+        original: &'a str,
+        offset: usize,
+        file: FileId,
+        options: &'a TokenizeOptions,
+    ) -> Self {
+        let data = SrcIterator::new(normalized.text())
+            .with_offset(normalized.normalized_offset(offset))
+            .with_edition(options.edition);
+        let mut lexer = Self {
+            data,
+            normalized,
+            original,
+            positions: PosTracker::new(original),
+            file,
+            options,
+            tokens: Vec::new(),
+            errors: Vec::new(),
+            trivia: Vec::new(),
+            shebang_start: None,
+            frontmatter_allowed: true,
+            seen_frontmatter: false,
+        };
+        // the byte order mark and shebang can only be at the very start of the file
+        if offset == 0 {
+            if lexer.data.eat("\u{FEFF}") && options.lossless {
+                let span = lexer.span(0..lexer.data.offset());
+                lexer.trivia.push(Trivia {
+                    kind: TriviaKind::ByteOrderMark,
+                    span,
+                    text: "\u{FEFF}".into(),
+                });
+            }
+            lexer.shebang_start = Some(lexer.data.offset());
+        }
+        lexer
+    }
+
+    /// Picks up lexing right where the last of `tokens` ends, as if the lexer had just produced
+    /// them. They must hold an ordinary token, the shebang and frontmatter are only lexed from the
+    /// start of the file.
+    pub(crate) fn resume(
+        normalized: &'a NormalizedSrc<'a>,
+        original: &'a str,
+        mut tokens: Vec<Token>,
+        file: FileId,
+        options: &'a TokenizeOptions,
+    ) -> Self {
+        let last = tokens
+            .last_mut()
+            .expect("resuming needs a token to resume after");
+        // decided again by whatever token comes next
+        last.spacing = Spacing::Alone;
+        let end = last.span.end;
+        let mut lexer = Self::new(normalized, original, end.offset, file, options);
+        lexer.positions = PosTracker::resume(original, end);
+        lexer.frontmatter_allowed = false;
+        lexer.tokens = tokens;
+        lexer
+    }
+
+    /// Where in the original text the lexer stops looking ahead to decide whether the file starts
+    /// with a shebang, before anything is lexed. `None` if there is nothing to decide.
+    pub(crate) fn shebang_lookahead_end(&self) -> Option<usize> {
+        let after = shebang_lookahead(self.data.with_offset(self.shebang_start?))?;
+        if after.starts_with("/") {
+            // whether that's a comment can depend on anything up to the end of the file, like an
+            // unclosed block comment or a bare CR in what turns out to be a doc comment
+            return Some(self.original.len());
+        }
+        Some(self.normalized.original_offset(after.offset()))
+    }
+
+    /// Whether the lexer is past the point where the shebang and frontmatter can show up
+    pub(crate) fn past_start(&self) -> bool {
+        !self.frontmatter_allowed
+    }
+
+    fn span(&mut self, range: Range<usize>) -> Span {
+        self.positions
+            .span(self.file, self.normalized.original_range(range))
+    }
+
+    /// Skips the whitespace in front of the next token or comment and gives where it starts, or
+    /// `None` at the end of the input
+    pub(crate) fn at_token(&mut self) -> Option<Pos> {
+        while let Some(chr) = self.data.peek()
+            && chr.is_whitespace()
+        {
+            let start = self.data.offset();
+            // CRLFs are LFs by now
+            let kind = if self.data.eat("\n") {
+                TriviaKind::Newline
             } else {
-                break;
+                while let Some(chr) = self.data.peek()
+                    && chr.is_whitespace()
+                    && chr != '\n'
+                {
+                    self.data.next();
+                }
+                TriviaKind::Whitespace
+            };
+            if self.options.lossless {
+                let span = self.span(start..self.data.offset());
+                let text = self.original[span.byte_range()].into();
+                self.trivia.push(Trivia { kind, span, text });
             }
         }
-        let res = Token::parse_token(data.clone());
-        match res {
-            Some((token, remaining)) => {
-                data = remaining;
-                // println!("Parsed token: {:?}", token);
-                if let Token::ReservedToken(a) = token {
-                    panic!("Parsed reserved token: {:?}", a);
+        self.data.peek()?;
+        let offset = self.normalized.original_offset(self.data.offset());
+        Some(self.positions.pos(offset))
+    }
+
+    /// Lexes the token that [`Lexer::at_token`] stopped at. `None` for a comment that doesn't go
+    /// in the stream.
+    pub(crate) fn lex(&mut self) -> Option<Token> {
+        let data = self.data;
+        let start = data.offset();
+        let kind = if Some(start) == self.shebang_start
+            && let Some(remaining) = lex_shebang(data)
+        {
+            self.data = remaining;
+            TokenKind::Shebang(remaining.slice_from(start).into())
+        } else if self.frontmatter_allowed && data.starts_with("---") {
+            let (frontmatter, remaining) =
+                frontmatter::lex_frontmatter(data, !self.seen_frontmatter);
+            self.data = remaining;
+            self.seen_frontmatter = true;
+            match frontmatter {
+                Ok(frontmatter) => TokenKind::Frontmatter(frontmatter),
+                Err((kind, error_range)) => {
+                    let span = self.span(error_range);
+                    self.errors.push(LexError { kind, span });
+                    TokenKind::Error(kind)
                 }
-                tokens.push(token);
             }
-            None => {
-                if data.peek().is_none() {
-                    return tokens.into_boxed_slice();
-                } else {
-                    println!("Failed to parse token");
-                    for _ in 0..1000 {
-                        if let Some(chr) = data.next() {
-                            print!("{}", chr);
-                        } else {
-                            break;
-                        }
+        } else {
+            self.frontmatter_allowed = false;
+            match TokenKind::parse_token(data) {
+                Some((TokenKind::ReservedToken(reserved), mut remaining)) => {
+                    let kind = LexErrorKind::from(&reserved);
+                    if let ReservedToken::ReservedTokenDoubleQuote(_)
+                    | ReservedToken::ReservedTokenSingleQuote(_)
+                    | ReservedToken::ReservedTokenPound(_) = reserved
+                    {
+                        // only the prefix is bad, lex whatever it is glued to on its own
+                        remaining = data.with_offset(remaining.offset() - 1);
                     }
-
-                    panic!("Failed to parse token in file {}", filename.to_str().unwrap());
+                    self.data = remaining;
+                    let span = self.span(start..remaining.offset());
+                    self.errors.push(LexError { kind, span });
+                    TokenKind::Error(kind)
+                }
+                Some((kind, remaining)) if !lex_error::is_truncated(&kind, remaining) => {
+                    self.data = remaining;
+                    kind
+                }
+                _ => {
+                    let (kind, error_range, remaining) = lex_error::recover(data);
+                    self.data = remaining;
+                    let span = self.span(error_range);
+                    self.errors.push(LexError { kind, span });
+                    TokenKind::Error(kind)
                 }
             }
+        };
+        let span = self.span(start..self.data.offset());
+        if !self.options.proc_macro
+            && let Some((kind, before_suffix)) = suffix::check_suffix(&kind)
+        {
+            // suffixes are identifiers, so they never span lines
+            let suffix = &self.normalized.text()[start + before_suffix..self.data.offset()];
+            let suffix_start = Pos {
+                offset: span.end.offset - suffix.len(),
+                line: span.end.line,
+                column: span.end.column - suffix.chars().count() as u32,
+            };
+            let span = Span::new(self.file, suffix_start, span.end);
+            self.errors.push(LexError { kind, span });
+        }
+
+        if let TokenKind::Comment(comment) = &kind {
+            if self.options.lossless {
+                let kind = match comment {
+                    Comment::LineComment(_) => TriviaKind::LineComment,
+                    Comment::BlockComment(_) => TriviaKind::BlockComment,
+                };
+                let text = self.original[span.byte_range()].into();
+                self.trivia.push(Trivia { kind, span, text });
+                return None;
+            }
+            if !self.options.keep_comments {
+                return None;
+            }
+        }
+        Some(Token {
+            kind,
+            span,
+            spacing: Spacing::Alone,
+            lossless: None,
+        })
+    }
+
+    /// Adds `token` to the stream, working out the spacing of the token before it and, in
+    /// lossless mode, the trivia in between
+    pub(crate) fn push(&mut self, mut token: Token) {
+        if let Some(prev) = self.tokens.last_mut()
+            && let TokenKind::Punctuation(prev_punctuation) = prev.kind
+            && !prev_punctuation.is_delimiter()
+            && match &token.kind {
+                TokenKind::Punctuation(punctuation) => !punctuation.is_delimiter(),
+                // proc_macro sees the `'` of a lifetime as punctuation of its own
+                TokenKind::LifetimeToken(_) => true,
+                _ => false,
+            }
+            && prev.span.end.offset == token.span.start.offset
+        {
+            prev.spacing = Spacing::Joint;
+        }
+        if self.options.lossless {
+            let text = self.original[token.span.byte_range()].into();
+            let trivia = std::mem::take(&mut self.trivia);
+            attach_trivia(&mut self.tokens, &mut token, trivia, text);
+        }
+        self.tokens.push(token);
+    }
+
+    /// Ends the stream with [`TokenKind::EndOfFile`] in lossless mode
+    pub(crate) fn finish(mut self) -> (Vec<Token>, Vec<LexError>) {
+        if self.options.lossless {
+            let end = self.data.offset();
+            let mut eof = Token {
+                kind: TokenKind::EndOfFile,
+                span: self.span(end..end),
+                spacing: Spacing::Alone,
+                lossless: None,
+            };
+            let trivia = std::mem::take(&mut self.trivia);
+            attach_trivia(&mut self.tokens, &mut eof, trivia, "".into());
+            self.tokens.push(eof);
+        }
+        self.into_parts()
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<Token>, Vec<LexError>) {
+        (self.tokens, self.errors)
+    }
+
+    /// Puts back the old tokens from the first one the lexer agrees with again, their spans
+    /// already moved to where they are now. Only the spacing and trivia in front of the first one
+    /// are worked out again.
+    pub(crate) fn resync(&mut self, rest: impl IntoIterator<Item = Token>) {
+        let mut rest = rest.into_iter();
+        if let Some(mut first) = rest.next() {
+            let trailing = first.lossless.take().map(|lossless| lossless.trailing);
+            self.push(first);
+            if let Some(trailing) = trailing
+                && let Some(lossless) = self.tokens.last_mut().unwrap().lossless.as_mut()
+            {
+                lossless.trailing = trailing;
+            }
+        }
+        self.tokens.extend(rest);
+    }
+}
+
+/// Gives the trivia since the previous token to that token and to `token`
+fn attach_trivia(tokens: &mut [Token], token: &mut Token, trivia: Vec<Trivia>, text: Box<str>) {
+    let (trailing, leading) = match tokens.last_mut().and_then(|prev| prev.lossless.as_mut()) {
+        Some(prev) => {
+            let (trailing, leading) = trivia::split_trivia(trivia);
+            prev.trailing = trailing.into_boxed_slice();
+            (Vec::new(), leading)
+        }
+        None => (Vec::new(), trivia),
+    };
+    token.lossless = Some(Box::new(LosslessText {
+        leading: leading.into_boxed_slice(),
+        text,
+        trailing: trailing.into_boxed_slice(),
+    }));
+}
+
+/// Lexes the shebang line at the start of `data` the way the reference has it: a `#!` that isn't
+/// followed by a `[`, not counting whitespace and ordinary comments, up to the end of the line
+fn lex_shebang(mut data: SrcIterator) -> Option<SrcIterator> {
+    if shebang_lookahead(data)?.starts_with("[") {
+        return None;
+    }
+    data.eat_while(|chr| chr != '\n');
+    Some(data)
+}
+
+/// Skips the `#!` at the start of `data` and the whitespace and ordinary comments after it, which
+/// is as far as the lexer has to look to tell a shebang from an inner attribute
+fn shebang_lookahead(mut data: SrcIterator) -> Option<SrcIterator> {
+    if !data.eat("#!") {
+        return None;
+    }
+    loop {
+        data.eat_while(char::is_whitespace);
+        match TokenKind::parse_token(data) {
+            Some((TokenKind::Comment(_), remaining)) => data = remaining,
+            _ => break,
         }
     }
+    Some(data)
+}
+
+#[derive(Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+    /// Only ever [`Spacing::Joint`] for punctuation directly followed by more punctuation or a
+    /// lifetime, with delimiters counting as neither
+    pub spacing: Spacing,
+    /// Only set in lossless mode
+    pub lossless: Option<Box<LosslessText>>,
+}
+
+impl Token {
+    /// Splits a compound punctuation token in two with [`Punctuation::split_first`]. The first
+    /// part is joint with the second, which keeps the spacing of the original. In lossless mode
+    /// the leading trivia stays with the first part and the trailing trivia goes to the second.
+    pub fn split_punctuation(&self) -> Option<(Token, Token)> {
+        let TokenKind::Punctuation(punctuation) = &self.kind else {
+            return None;
+        };
+        let (first, rest) = punctuation.split_first()?;
+        // punctuation is ascii and never crosses a line
+        let middle = Pos {
+            offset: self.span.start.offset + 1,
+            line: self.span.start.line,
+            column: self.span.start.column + 1,
+        };
+        let (first_lossless, rest_lossless) = match &self.lossless {
+            Some(lossless) => (
+                Some(Box::new(LosslessText {
+                    leading: lossless.leading.clone(),
+                    text: first.as_str().into(),
+                    trailing: Box::new([]),
+                })),
+                Some(Box::new(LosslessText {
+                    leading: Box::new([]),
+                    text: rest.as_str().into(),
+                    trailing: lossless.trailing.clone(),
+                })),
+            ),
+            None => (None, None),
+        };
+        Some((
+            Token {
+                kind: TokenKind::Punctuation(first),
+                span: Span::new(self.span.file, self.span.start, middle),
+                spacing: Spacing::Joint,
+                lossless: first_lossless,
+            },
+            Token {
+                kind: TokenKind::Punctuation(rest),
+                span: Span::new(self.span.file, middle, self.span.end),
+                spacing: self.spacing,
+                lossless: rest_lossless,
+            },
+        ))
+    }
+
+    /// Splits a float literal that follows a field access `.` into the tuple indices and dots it
+    /// is made of, like rustc does so that `x.0.1` is two field accesses: `0.1` becomes `0`, `.`
+    /// and `1`, each with its own span. `None` for anything but a float literal. A tuple index
+    /// can't have an exponent or a suffix, so `x.1e3` and `x.0.1f32` are errors. In lossless mode
+    /// the leading trivia goes to the first part and the trailing trivia to the last.
+    pub fn split_tuple_index(&self) -> Option<Result<Box<[Token]>, LexError>> {
+        let TokenKind::FloatLiteral(literal) = &self.kind else {
+            return None;
+        };
+        let source = literal.source();
+        // without the suffix float literals are ascii and never cross a line
+        let pos = |i: usize| Pos {
+            offset: self.span.start.offset + i,
+            line: self.span.start.line,
+            column: self.span.start.column + i as u32,
+        };
+        if source.contains(['e', 'E']) {
+            let (kind, span) = (LexErrorKind::ExponentInTupleIndex, self.span);
+            return Some(Err(LexError { kind, span }));
+        }
+        if literal.suffix().is_some() {
+            let kind = LexErrorKind::SuffixOnTupleIndex;
+            let span = Span::new(self.span.file, pos(source.len()), self.span.end);
+            return Some(Err(LexError { kind, span }));
+        }
+
+        let index = |text: &str| {
+            let (index, _) = IntegerLiteral::parse_token(SrcIterator::new(text))
+                .expect("the digits around the dot are an integer");
+            TokenKind::IntegerLiteral(index)
+        };
+        let dot = source
+            .find('.')
+            .expect("float literals without an exponent have a dot");
+        let mut parts = vec![
+            (0..dot, index(&source[..dot])),
+            (dot..dot + 1, TokenKind::Punctuation(Punctuation::SingleDot)),
+        ];
+        if dot + 1 < source.len() {
+            parts.push((dot + 1..source.len(), index(&source[dot + 1..])));
+        }
+
+        let last = parts.len() - 1;
+        let tokens = parts.into_iter().enumerate().map(|(i, (range, kind))| {
+            let lossless = self.lossless.as_ref().map(|lossless| {
+                Box::new(LosslessText {
+                    leading: match i {
+                        0 => lossless.leading.clone(),
+                        _ => Box::new([]),
+                    },
+                    text: source[range.clone()].into(),
+                    trailing: match i == last {
+                        true => lossless.trailing.clone(),
+                        false => Box::new([]),
+                    },
+                })
+            });
+            Token {
+                kind,
+                span: Span::new(self.span.file, pos(range.start), pos(range.end)),
+                spacing: match i == last {
+                    true => self.spacing,
+                    false => Spacing::Alone,
+                },
+                lossless,
+            }
+        });
+        Some(Ok(tokens.collect()))
+    }
 }
 
 #[derive(ParseEnumToken, Debug)]
-pub enum Token {
+pub enum TokenKind {
+    DocComment(DocComment),
     Comment(Comment),
     ReservedToken(ReservedToken),
     RawIdentifier(RawIdentifier),
@@ -115,48 +602,287 @@ pub enum Token {
     IntegerLiteral(IntegerLiteral),
     LifetimeToken(LifetimeToken),
     Punctuation(Punctuation),
+    Keyword(Keyword),
     IdentifierOrKeyword(IdentifierOrKeyword),
+    /// `#!` line at the very start of the file, like `#!/usr/bin/env rustx`. Holds the whole
+    /// line without the line break.
+    #[skip]
+    Shebang(Box<str>),
+    /// Only lexed at the start of the file, see [`frontmatter::Frontmatter`]
+    #[skip]
+    Frontmatter(Frontmatter),
+    /// Stands in for a token that failed to lex
+    #[skip]
+    Error(LexErrorKind),
+    /// Only emitted in lossless mode, carries the trivia at the end of the file
+    #[skip]
+    EndOfFile,
 }
 
-#[derive(ParseEnumToken, Debug)]
-pub enum Comment {
-    LineComment(LineComment),
-    BlockComment(BlockComment),
-}
-
-#[derive(Debug)]
-pub struct LineComment;
+impl TokenKind {
+    /// Tokens that don't mean anything to the parser, like ordinary comments, the shebang and
+    /// frontmatter
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            Self::Comment(_) | Self::Shebang(_) | Self::Frontmatter(_)
+        )
+    }
 
-impl tokenizer_trait::Token for LineComment {
-    fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        if data.next()? != '/' || data.next()? != '/' {
-            return None;
+    /// Keyword this token is, weak keywords and `'static` included
+    pub fn keyword(&self) -> Option<Keyword> {
+        match self {
+            Self::Keyword(keyword) => Some(*keyword),
+            Self::IdentifierOrKeyword(ident) => ident.weak_keyword(),
+            Self::LifetimeToken(lifetime) => lifetime.keyword(),
+            _ => None,
         }
-        while let Some(chr) = data.peek() {
-            if *chr == '\n' {
-                break;
-            }
-            data.next();
-        }
-        Some((Self, data))
     }
 }
 
-#[derive(Debug)]
-pub struct BlockComment;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl tokenizer_trait::Token for BlockComment {
-    fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        if data.next()? != '/' || data.next()? != '*' {
-            return None;
+    /// The float literal in `src` split as a tuple index, each part as its text and byte range
+    fn split_tuple_index(src: &str) -> Result<Vec<(String, Range<usize>)>, LexError> {
+        let options = TokenizeOptions {
+            proc_macro: true,
+            ..TokenizeOptions::default()
+        };
+        let tokens = tokenize(SrcIterator::new(src), FileId::default(), &options).unwrap();
+        let parts = tokens
+            .iter()
+            .find_map(Token::split_tuple_index)
+            .expect("a float literal")?;
+        let parts = parts
+            .iter()
+            .map(|part| {
+                let text = match &part.kind {
+                    TokenKind::IntegerLiteral(index) => index.digits(),
+                    TokenKind::Punctuation(punctuation) => punctuation.as_str(),
+                    kind => panic!("expected an index or a dot, got {kind:?}"),
+                };
+                assert_eq!(&src[part.span.byte_range()], text);
+                (text.to_string(), part.span.byte_range())
+            })
+            .collect();
+        Ok(parts)
+    }
+
+    fn parts(parts: &[(&str, Range<usize>)]) -> Vec<(String, Range<usize>)> {
+        parts
+            .iter()
+            .map(|(text, range)| (text.to_string(), range.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn tuple_index() {
+        assert_eq!(
+            split_tuple_index("x.0.1"),
+            Ok(parts(&[("0", 2..3), (".", 3..4), ("1", 4..5)]))
+        );
+        assert_eq!(
+            split_tuple_index("x.12.\n"),
+            Ok(parts(&[("12", 2..4), (".", 4..5)]))
+        );
+        let src = "x\n  .0.1";
+        let tokens = tokenize(
+            SrcIterator::new(src),
+            FileId::default(),
+            &Default::default(),
+        );
+        let float = tokens.unwrap().into_iter().last().unwrap();
+        let parts = float.split_tuple_index().unwrap().unwrap();
+        let columns: Vec<_> = parts.iter().map(|part| part.span.start.column).collect();
+        assert_eq!(columns, [4, 5, 6]);
+        assert!(parts.iter().all(|part| part.span.start.line == 2));
+    }
+
+    #[test]
+    fn tuple_index_exponent() {
+        let error = split_tuple_index("x.1e2").unwrap_err();
+        assert_eq!(error.kind, LexErrorKind::ExponentInTupleIndex);
+        assert_eq!(error.span.byte_range(), 2..5);
+    }
+
+    #[test]
+    fn tuple_index_suffix() {
+        let error = split_tuple_index("x.0.1u8").unwrap_err();
+        assert_eq!(error.kind, LexErrorKind::SuffixOnTupleIndex);
+        assert_eq!(error.span.byte_range(), 5..7);
+    }
+
+    #[test]
+    fn tuple_index_lossless() {
+        let options = TokenizeOptions {
+            lossless: true,
+            ..TokenizeOptions::default()
+        };
+        let src = "x. /* a */ 0.1 // b\n";
+        let tokens = tokenize(SrcIterator::new(src), FileId::default(), &options).unwrap();
+        let tokens: Vec<Token> = tokens
+            .into_vec()
+            .into_iter()
+            .flat_map(|token| match token.split_tuple_index() {
+                Some(parts) => parts.unwrap().into_vec(),
+                None => vec![token],
+            })
+            .collect();
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(trivia::print_lossless(&tokens).as_deref(), Some(src));
+    }
+
+    #[test]
+    fn tuple_index_only_splits_floats() {
+        let tokens = tokenize(
+            SrcIterator::new("x.0"),
+            FileId::default(),
+            &Default::default(),
+        );
+        assert!(
+            tokens
+                .unwrap()
+                .iter()
+                .all(|token| token.split_tuple_index().is_none())
+        );
+    }
+
+    /// Every token in `src` lexed in `edition`, as what it was lexed as and its text
+    fn lex_in(src: &str, edition: Edition) -> Vec<String> {
+        let options = TokenizeOptions {
+            edition,
+            ..TokenizeOptions::default()
+        };
+        let tokens = match tokenize(SrcIterator::new(src), FileId::default(), &options) {
+            Ok(tokens) => tokens,
+            Err(error) => error.tokens,
+        };
+        tokens
+            .iter()
+            .map(|token| {
+                let kind = match &token.kind {
+                    TokenKind::Keyword(_) => "keyword".to_string(),
+                    TokenKind::IdentifierOrKeyword(_) => "ident".to_string(),
+                    TokenKind::RawIdentifier(_) => "raw ident".to_string(),
+                    TokenKind::LifetimeToken(_) => "lifetime".to_string(),
+                    TokenKind::Punctuation(_) => "punct".to_string(),
+                    TokenKind::CharLiteral(_) => "char".to_string(),
+                    TokenKind::StringLiteral(_) => "string".to_string(),
+                    TokenKind::RawStringLiteral(_) => "raw string".to_string(),
+                    TokenKind::CStringLiteral(_) => "c string".to_string(),
+                    TokenKind::RawCStringLiteral(_) => "raw c string".to_string(),
+                    TokenKind::Error(kind) => format!("{kind:?}"),
+                    kind => panic!("unexpected {kind:?}"),
+                };
+                format!("{kind} {}", &src[token.span.byte_range()])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn c_strings_since_2021() {
+        let src = r#"c"x" cr"y""#;
+        for edition in [Edition::Edition2015, Edition::Edition2018] {
+            assert_eq!(
+                lex_in(src, edition),
+                ["ident c", "string \"x\"", "ident cr", "string \"y\""]
+            );
         }
-        loop {
-            let chr = data.next()?;
-            if chr == '*' && *data.peek()? == '/' {
-                data.next();
-                break;
-            }
+        for edition in [Edition::Edition2021, Edition::Edition2024] {
+            assert_eq!(
+                lex_in(src, edition),
+                ["c string c\"x\"", "raw c string cr\"y\""]
+            );
         }
-        Some((Self, data))
+    }
+
+    #[test]
+    fn reserved_prefixes_since_2021() {
+        let src = "z\"w\" k'a' f#x";
+        assert_eq!(
+            lex_in(src, Edition::Edition2018),
+            [
+                "ident z",
+                "string \"w\"",
+                "ident k",
+                "char 'a'",
+                "ident f",
+                "punct #",
+                "ident x"
+            ]
+        );
+        // only the prefix is an error, what follows it is lexed on its own
+        assert_eq!(
+            lex_in(src, Edition::Edition2021),
+            [
+                "ReservedPrefix z",
+                "string \"w\"",
+                "ReservedPrefix k",
+                "char 'a'",
+                "ReservedPrefix f",
+                "punct #",
+                "ident x"
+            ]
+        );
+    }
+
+    #[test]
+    fn pounds_since_2024() {
+        let src = "## #\"x\" ###";
+        assert_eq!(
+            lex_in(src, Edition::Edition2021),
+            [
+                "punct #",
+                "punct #",
+                "punct #",
+                "string \"x\"",
+                "punct #",
+                "punct #",
+                "punct #"
+            ]
+        );
+        assert_eq!(
+            lex_in(src, Edition::Edition2024),
+            [
+                "ReservedPounds ##",
+                "ReservedGuardedString #\"x\"",
+                "ReservedPounds ###"
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_lifetimes_since_2021() {
+        assert_eq!(
+            lex_in("'r#a", Edition::Edition2018),
+            ["lifetime 'r", "punct #", "ident a"]
+        );
+        assert_eq!(lex_in("'r#a", Edition::Edition2021), ["lifetime 'r#a"]);
+    }
+
+    #[test]
+    fn keywords_per_edition() {
+        let src = "async dyn try gen await union";
+        let lexed = |edition| -> Vec<String> {
+            lex_in(src, edition)
+                .into_iter()
+                .map(|token| token.split(' ').next().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            lexed(Edition::Edition2015),
+            ["ident", "ident", "ident", "ident", "ident", "ident"]
+        );
+        let since_2018 = ["keyword", "keyword", "keyword", "ident", "keyword", "ident"];
+        assert_eq!(lexed(Edition::Edition2018), since_2018);
+        assert_eq!(lexed(Edition::Edition2021), since_2018);
+        assert_eq!(
+            lexed(Edition::Edition2024),
+            [
+                "keyword", "keyword", "keyword", "keyword", "keyword", "ident"
+            ]
+        );
     }
 }
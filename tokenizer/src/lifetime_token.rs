@@ -1,6 +1,11 @@
-use tokenizer_trait::SrcIterator;
+use tokenizer_trait::{Edition, SrcIterator};
 
-use crate::{IdentifierOrKeyword, identifier_or_keyword::NonKeywordIdentifier};
+use crate::{
+    IdentifierOrKeyword,
+    identifier_or_keyword::NonKeywordIdentifier,
+    keyword::Keyword,
+    symbol::{Symbol, kw},
+};
 
 #[derive(Debug)]
 pub enum LifetimeToken {
@@ -8,10 +13,32 @@ pub enum LifetimeToken {
     Raw(RawLifetime),
 }
 
+impl LifetimeToken {
+    /// [`Keyword::StaticLifetime`] for `'static`
+    pub fn keyword(&self) -> Option<Keyword> {
+        match self {
+            Self::Regular(ident) if ident.symbol() == kw::Static => Some(Keyword::StaticLifetime),
+            _ => None,
+        }
+    }
+
+    /// The name without the `'` and, for raw lifetimes, the `r#`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Regular(ident) => ident.parsed(),
+            Self::Raw(raw) => raw.name(),
+        }
+    }
+
+    pub fn is_raw(&self) -> bool {
+        matches!(self, Self::Raw(_))
+    }
+}
+
 impl tokenizer_trait::Token for LifetimeToken {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
         //try raw
-        if let Some(raw_lifetime) = RawLifetime::parse_token(data.clone()) {
+        if let Some(raw_lifetime) = RawLifetime::parse_token(data) {
             return Some((Self::Raw(raw_lifetime.0), raw_lifetime.1));
         }
         //try regular
@@ -19,7 +46,7 @@ impl tokenizer_trait::Token for LifetimeToken {
         if first_chr != '\'' {
             return None;
         }
-        let mut regular_lifetime = IdentifierOrKeyword::parse_token(data)?;
+        let regular_lifetime = IdentifierOrKeyword::parse_token(data)?;
         if let Some('\'') = regular_lifetime.1.peek() {
             return None;
         }
@@ -36,7 +63,7 @@ pub enum LifetimeOrLabel {
 impl tokenizer_trait::Token for LifetimeOrLabel {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
         //try raw
-        if let Some(raw_lifetime) = RawLifetime::parse_token(data.clone()) {
+        if let Some(raw_lifetime) = RawLifetime::parse_token(data) {
             return Some((Self::Raw(raw_lifetime.0), raw_lifetime.1));
         }
         //try regular
@@ -44,7 +71,7 @@ impl tokenizer_trait::Token for LifetimeOrLabel {
         if first_chr != '\'' {
             return None;
         }
-        let mut regular_lifetime = NonKeywordIdentifier::parse_token(data)?;
+        let regular_lifetime = NonKeywordIdentifier::parse_token(data)?;
         if let Some('\'') = regular_lifetime.1.peek() {
             return None;
         }
@@ -57,13 +84,24 @@ pub struct RawLifetime {
     inner: IdentifierOrKeyword,
 }
 
+impl RawLifetime {
+    /// The name without the `'r#`
+    pub fn name(&self) -> &'static str {
+        self.inner.parsed()
+    }
+}
+
 impl tokenizer_trait::Token for RawLifetime {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        // raw lifetimes exist since 2021
+        if data.edition() < Edition::Edition2021 {
+            return None;
+        }
         if data.next()? != '\'' || data.next()? != 'r' || data.next()? != '#' {
             return None;
         }
 
-        let mut inner = IdentifierOrKeyword::parse_token(data)?;
+        let inner = IdentifierOrKeyword::parse_token(data)?;
         if let Some('\'') = inner.1.peek() {
             return None;
         }
@@ -76,21 +114,23 @@ pub struct ReservedRawLifetime;
 
 impl tokenizer_trait::Token for ReservedRawLifetime {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        // raw lifetimes exist since 2021
+        if data.edition() < Edition::Edition2021 {
+            return None;
+        }
         if data.next()? != '\'' || data.next()? != 'r' || data.next()? != '#' {
             return None;
         }
-        const RESERVED_RAW_LIFETIME_TABLE: &[&str] = &["_", "crate", "self", "Self", "super"];
-        for reserved_raw_lifetime in RESERVED_RAW_LIFETIME_TABLE {
-            let zipped = reserved_raw_lifetime.chars().zip(data.clone());
-            if zipped.clone().all(|(a, b)| a == b) && zipped.count() == reserved_raw_lifetime.len() {
-                (0..reserved_raw_lifetime.len()).for_each(|_| {
-                    data.next();
-                });
-                return Some((
-                    Self,
-                    data,
-                ));
-            }
+        const RESERVED_RAW_LIFETIME_TABLE: &[Symbol] = &[
+            kw::Underscore,
+            kw::Crate,
+            kw::SelfLower,
+            kw::SelfUpper,
+            kw::Super,
+        ];
+        let (ident, data) = IdentifierOrKeyword::parse_token(data)?;
+        if RESERVED_RAW_LIFETIME_TABLE.contains(&ident.symbol()) {
+            return Some((Self, data));
         }
         None
     }
@@ -0,0 +1,61 @@
+use std::{borrow::Cow, ops::Range};
+
+/// Source text with every CRLF turned into a plain LF, which is what rustc lexes. Offsets into the
+/// normalized text map back to the original text, so spans can point at the bytes on disk.
+#[derive(Debug, Clone)]
+pub struct NormalizedSrc<'a> {
+    text: Cow<'a, str>,
+    /// Offset in the normalized text of the LF of every CRLF, in order
+    crlfs: Box<[usize]>,
+}
+
+impl<'a> NormalizedSrc<'a> {
+    pub fn new(src: &'a str) -> Self {
+        if !src.contains("\r\n") {
+            return Self {
+                text: Cow::Borrowed(src),
+                crlfs: Box::new([]),
+            };
+        }
+        let mut text = String::with_capacity(src.len());
+        let mut crlfs = Vec::new();
+        let mut rest = src;
+        while let Some(cr) = rest.find("\r\n") {
+            text.push_str(&rest[..cr]);
+            crlfs.push(text.len());
+            rest = &rest[cr + 1..];
+        }
+        text.push_str(rest);
+        Self {
+            text: Cow::Owned(text),
+            crlfs: crlfs.into_boxed_slice(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Offset in the original text of `offset` in the normalized text. The LF of a CRLF maps to
+    /// its CR, so a range ending at a line break stops before the whole CRLF and a range starting
+    /// at it covers the whole CRLF.
+    pub fn original_offset(&self, offset: usize) -> usize {
+        offset + self.crlfs.partition_point(|lf| *lf < offset)
+    }
+
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.original_offset(range.start)..self.original_offset(range.end)
+    }
+
+    /// Offset in the normalized text of `offset` in the original text
+    pub fn normalized_offset(&self, offset: usize) -> usize {
+        // the CR of the i-th CRLF was at `lf + i` in the original text
+        let removed = self
+            .crlfs
+            .iter()
+            .enumerate()
+            .take_while(|(i, lf)| *lf + i < offset)
+            .count();
+        offset - removed
+    }
+}
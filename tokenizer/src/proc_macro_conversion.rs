@@ -0,0 +1,494 @@
+use std::{fmt::Display, str::FromStr};
+
+use proc_macro2 as pm;
+use tokenizer_trait::{Edition, SrcIterator, Token as TokenTrait};
+
+use crate::{
+    Token, TokenKind,
+    comment::{DocComment, DocCommentKind, DocStyle},
+    lex_error::{LexError, LexErrorKind},
+    punctuation::{Punctuation, Spacing},
+    span::{FileId, Pos, Span},
+    token_tree::{Delimiter, Group, TokenTree},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// An error token, which proc_macro2 has no equivalent of, or a proc_macro2 token whose text
+    /// doesn't lex as exactly one token
+    Lex(LexError),
+    /// proc_macro2 doesn't accept the text of this literal
+    Literal(Span),
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lex(error) => write!(f, "{}", error),
+            Self::Literal(span) => write!(f, "{}: literal not accepted by proc_macro2", span),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Comments, the shebang, frontmatter and the end of file token are left out. Doc comments become
+/// `#[doc = "..."]` attributes, the same as rustc hands them to proc macros. proc_macro2 can't
+/// make spans for arbitrary source positions, so every token gets `Span::call_site()`.
+pub fn to_token_stream(trees: &[TokenTree]) -> Result<pm::TokenStream, ConversionError> {
+    let mut out = Vec::new();
+    for tree in trees {
+        push_tree(&mut out, tree)?;
+    }
+    Ok(out.into_iter().collect())
+}
+
+fn push_tree(out: &mut Vec<pm::TokenTree>, tree: &TokenTree) -> Result<(), ConversionError> {
+    let token = match tree {
+        TokenTree::Leaf(token) => token,
+        TokenTree::Group(group) => {
+            let delimiter = match group.delimiter {
+                Delimiter::Parenthesis => pm::Delimiter::Parenthesis,
+                Delimiter::Brace => pm::Delimiter::Brace,
+                Delimiter::Bracket => pm::Delimiter::Bracket,
+            };
+            let stream = to_token_stream(&group.stream)?;
+            out.push(pm::Group::new(delimiter, stream).into());
+            return Ok(());
+        }
+    };
+    let span = pm::Span::call_site();
+
+    match &token.kind {
+        TokenKind::Comment(_)
+        | TokenKind::Shebang(_)
+        | TokenKind::Frontmatter(_)
+        | TokenKind::EndOfFile => {}
+        TokenKind::Error(kind) => {
+            return Err(ConversionError::Lex(LexError {
+                kind: *kind,
+                span: token.span,
+            }));
+        }
+        TokenKind::ReservedToken(reserved) => {
+            return Err(ConversionError::Lex(LexError {
+                kind: LexErrorKind::from(reserved),
+                span: token.span,
+            }));
+        }
+        TokenKind::DocComment(doc) => {
+            out.push(pm::Punct::new('#', pm::Spacing::Alone).into());
+            if doc.style() == DocStyle::Inner {
+                out.push(pm::Punct::new('!', pm::Spacing::Alone).into());
+            }
+            let attribute: [pm::TokenTree; 3] = [
+                pm::Ident::new("doc", span).into(),
+                pm::Punct::new('=', pm::Spacing::Alone).into(),
+                pm::Literal::string(doc.text()).into(),
+            ];
+            let attribute = attribute.into_iter().collect();
+            out.push(pm::Group::new(pm::Delimiter::Bracket, attribute).into());
+        }
+        TokenKind::Keyword(keyword) => out.push(pm::Ident::new(keyword.as_str(), span).into()),
+        TokenKind::IdentifierOrKeyword(ident) => {
+            out.push(pm::Ident::new(ident.parsed(), span).into())
+        }
+        TokenKind::RawIdentifier(ident) => {
+            out.push(pm::Ident::new_raw(ident.parsed(), span).into())
+        }
+        TokenKind::LifetimeToken(lifetime) => {
+            out.push(pm::Punct::new('\'', pm::Spacing::Joint).into());
+            let name = match lifetime.is_raw() {
+                true => pm::Ident::new_raw(lifetime.name(), span),
+                false => pm::Ident::new(lifetime.name(), span),
+            };
+            out.push(name.into());
+        }
+        TokenKind::Punctuation(punctuation) => {
+            let mut chars = punctuation.as_str().chars().peekable();
+            while let Some(chr) = chars.next() {
+                let spacing = match (chars.peek(), token.spacing) {
+                    (Some(_), _) | (None, Spacing::Joint) => pm::Spacing::Joint,
+                    (None, Spacing::Alone) => pm::Spacing::Alone,
+                };
+                out.push(pm::Punct::new(chr, spacing).into());
+            }
+        }
+        kind => {
+            let text = literal_text(kind).expect("every other token is a literal");
+            let literal =
+                pm::Literal::from_str(&text).map_err(|_| ConversionError::Literal(token.span))?;
+            out.push(literal.into());
+        }
+    }
+    Ok(())
+}
+
+/// Source text of a literal token, suffix included
+fn literal_text(kind: &TokenKind) -> Option<String> {
+    let (source, suffix) = match kind {
+        TokenKind::CharLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::StringLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::RawStringLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::ByteLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::ByteStringLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::RawByteStringLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::CStringLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::RawCStringLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::FloatLiteral(literal) => (literal.source(), literal.suffix()),
+        TokenKind::IntegerLiteral(literal) => (literal.source(), literal.suffix()),
+        _ => return None,
+    };
+    Some(format!("{}{}", source, suffix.unwrap_or("")))
+}
+
+/// Every proc_macro2 token is lexed again from its text with `edition`, so keywords and literals
+/// come out exactly as if the source had been tokenized directly. Lifetimes and runs of joint
+/// punctuation are glued back into single tokens, `#[doc = "..."]` attributes turn back into doc
+/// comments and the contents of invisible groups are inlined.
+///
+/// Spans come from proc_macro2's `span-locations`, which only knows real positions for streams
+/// parsed from a string.
+pub fn from_token_stream(
+    stream: pm::TokenStream,
+    file: FileId,
+    edition: Edition,
+) -> Result<Box<[TokenTree]>, ConversionError> {
+    let mut out = Vec::new();
+    push_stream(&mut out, stream, file, edition)?;
+    Ok(out.into_boxed_slice())
+}
+
+fn push_stream(
+    out: &mut Vec<TokenTree>,
+    stream: pm::TokenStream,
+    file: FileId,
+    edition: Edition,
+) -> Result<(), ConversionError> {
+    let trees: Vec<pm::TokenTree> = stream.into_iter().collect();
+    let mut i = 0;
+    while i < trees.len() {
+        match &trees[i] {
+            pm::TokenTree::Group(group) => {
+                let delimiter = match group.delimiter() {
+                    pm::Delimiter::Parenthesis => Delimiter::Parenthesis,
+                    pm::Delimiter::Brace => Delimiter::Brace,
+                    pm::Delimiter::Bracket => Delimiter::Bracket,
+                    pm::Delimiter::None => {
+                        push_stream(out, group.stream(), file, edition)?;
+                        i += 1;
+                        continue;
+                    }
+                };
+                let mut stream = Vec::new();
+                push_stream(&mut stream, group.stream(), file, edition)?;
+                out.push(TokenTree::Group(Group {
+                    delimiter,
+                    open_span: convert_span(group.span_open(), file),
+                    close_span: convert_span(group.span_close(), file),
+                    stream: stream.into_boxed_slice(),
+                }));
+                i += 1;
+            }
+            pm::TokenTree::Ident(ident) => {
+                let span = convert_span(ident.span(), file);
+                out.push(leaf(&ident.to_string(), span, edition)?);
+                i += 1;
+            }
+            pm::TokenTree::Literal(literal) => {
+                let span = convert_span(literal.span(), file);
+                let text = literal.to_string();
+                // proc_macro2 allows negative literals, the lexer never produces them
+                let text = match text.strip_prefix('-') {
+                    Some(text) => {
+                        out.push(leaf("-", span, edition)?);
+                        text.to_string()
+                    }
+                    None => text,
+                };
+                out.push(leaf(&text, span, edition)?);
+                i += 1;
+            }
+            pm::TokenTree::Punct(punct) => {
+                if let Some((doc, len)) = doc_attribute(&trees[i..], file, edition) {
+                    out.push(TokenTree::Leaf(doc));
+                    i += len;
+                } else if punct.as_char() == '\''
+                    && let Some(pm::TokenTree::Ident(ident)) = trees.get(i + 1)
+                {
+                    let span =
+                        convert_span(punct.span(), file).to(convert_span(ident.span(), file));
+                    out.push(leaf(&format!("'{}", ident), span, edition)?);
+                    i += 2;
+                } else {
+                    i += push_punctuation(out, &trees[i..], file, edition)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lexes the run of joint punctuation at the start of `trees` into punctuation tokens and returns
+/// how many proc_macro2 tokens it took
+fn push_punctuation(
+    out: &mut Vec<TokenTree>,
+    trees: &[pm::TokenTree],
+    file: FileId,
+    edition: Edition,
+) -> Result<usize, ConversionError> {
+    let mut puncts = Vec::new();
+    for tree in trees {
+        let pm::TokenTree::Punct(punct) = tree else {
+            break;
+        };
+        // a `'` starts a lifetime, never more punctuation
+        if punct.as_char() == '\'' && !puncts.is_empty() {
+            break;
+        }
+        puncts.push(punct);
+        if punct.spacing() == pm::Spacing::Alone {
+            break;
+        }
+    }
+
+    let text: String = puncts.iter().map(|punct| punct.as_char()).collect();
+    let mut data = SrcIterator::new(&text).with_edition(edition);
+    while data.peek().is_some() {
+        let start = data.offset();
+        let first = convert_span(puncts[start].span(), file);
+        let Some((punctuation, remaining)) = Punctuation::parse_token(data) else {
+            return Err(ConversionError::Lex(LexError {
+                kind: LexErrorKind::StrayCharacter(data.peek().unwrap()),
+                span: first,
+            }));
+        };
+        data = remaining;
+        let last = convert_span(puncts[data.offset() - 1].span(), file);
+        out.push(TokenTree::Leaf(Token {
+            kind: TokenKind::Punctuation(punctuation),
+            span: first.to(last),
+            spacing: match (data.peek(), puncts.last().unwrap().spacing()) {
+                (Some(_), _) | (None, pm::Spacing::Joint) => Spacing::Joint,
+                (None, pm::Spacing::Alone) => Spacing::Alone,
+            },
+            lossless: None,
+        }));
+    }
+    Ok(puncts.len())
+}
+
+/// `#[doc = "..."]` or `#![doc = "..."]` at the start of `trees`, as a doc comment and the number
+/// of proc_macro2 tokens it took
+fn doc_attribute(
+    trees: &[pm::TokenTree],
+    file: FileId,
+    edition: Edition,
+) -> Option<(Token, usize)> {
+    let [pm::TokenTree::Punct(hash), rest @ ..] = trees else {
+        return None;
+    };
+    if hash.as_char() != '#' {
+        return None;
+    }
+    let (style, group) = match rest {
+        [pm::TokenTree::Punct(bang), pm::TokenTree::Group(group), ..] if bang.as_char() == '!' => {
+            (DocStyle::Inner, group)
+        }
+        [pm::TokenTree::Group(group), ..] => (DocStyle::Outer, group),
+        _ => return None,
+    };
+    if group.delimiter() != pm::Delimiter::Bracket {
+        return None;
+    }
+    let attribute: Vec<pm::TokenTree> = group.stream().into_iter().collect();
+    let [
+        pm::TokenTree::Ident(name),
+        pm::TokenTree::Punct(equals),
+        pm::TokenTree::Literal(literal),
+    ] = attribute.as_slice()
+    else {
+        return None;
+    };
+    if name != "doc" || equals.as_char() != '=' {
+        return None;
+    }
+    let text = literal.to_string();
+    let data = SrcIterator::new(&text).with_edition(edition);
+    let (text, suffix) = match TokenKind::parse_token(data)? {
+        (TokenKind::StringLiteral(literal), rest) if rest.peek().is_none() => {
+            (literal.value().to_string(), literal.suffix())
+        }
+        (TokenKind::RawStringLiteral(literal), rest) if rest.peek().is_none() => {
+            (literal.value().to_string(), literal.suffix())
+        }
+        _ => return None,
+    };
+    if suffix.is_some() {
+        return None;
+    }
+
+    let kind = match text.contains('\n') {
+        true => DocCommentKind::Block,
+        false => DocCommentKind::Line,
+    };
+    let span = convert_span(hash.span(), file).to(convert_span(group.span_close(), file));
+    let token = Token {
+        kind: TokenKind::DocComment(DocComment::new(style, kind, &text)),
+        span,
+        spacing: Spacing::Alone,
+        lossless: None,
+    };
+    let len = match style {
+        DocStyle::Outer => 2,
+        DocStyle::Inner => 3,
+    };
+    Some((token, len))
+}
+
+/// Lexes `text`, which has to be exactly one token
+fn leaf(text: &str, span: Span, edition: Edition) -> Result<TokenTree, ConversionError> {
+    let data = SrcIterator::new(text).with_edition(edition);
+    let kind = match TokenKind::parse_token(data) {
+        Some((TokenKind::ReservedToken(reserved), _)) => LexErrorKind::from(&reserved),
+        Some((kind, rest)) if rest.peek().is_none() => {
+            return Ok(TokenTree::Leaf(Token {
+                kind,
+                span,
+                spacing: Spacing::Alone,
+                lossless: None,
+            }));
+        }
+        Some((_, rest)) => LexErrorKind::StrayCharacter(rest.peek().unwrap()),
+        None => LexErrorKind::StrayCharacter(data.peek().unwrap()),
+    };
+    Err(ConversionError::Lex(LexError { kind, span }))
+}
+
+fn convert_span(span: pm::Span, file: FileId) -> Span {
+    let range = span.byte_range();
+    let (start, end) = (span.start(), span.end());
+    Span::new(
+        file,
+        Pos {
+            offset: range.start,
+            line: start.line as u32,
+            column: start.column as u32 + 1,
+        },
+        Pos {
+            offset: range.end,
+            line: end.line as u32,
+            column: end.column as u32 + 1,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        TokenizeOptions,
+        token_tree::{build_token_trees, flatten_token_trees},
+        tokenize,
+    };
+
+    const SRC: &str = r##"//! inner doc
+/// outer doc
+/** block
+doc */
+fn r#fn<'a, 'r#b>(x: &'a [u8; 2]) -> Option<()> {
+    let _ = ('c', "s\n", r#"r"#, b'b', b"bs", br"rb", c"c", cr#"rc"#);
+    let _ = (1, 1.5f32, 0x1Fu8, 1e3, 2.);
+    x.0 += 1 << 2; a::b!{ ..= } #[x] 'static
+}"##;
+
+    fn trees(src: &str) -> Box<[TokenTree]> {
+        let tokens = tokenize(
+            SrcIterator::new(src),
+            FileId::default(),
+            &TokenizeOptions::default(),
+        )
+        .unwrap();
+        build_token_trees(tokens).unwrap()
+    }
+
+    /// Kind, span and spacing of every token, which is all the two directions have to agree on
+    fn describe(trees: Box<[TokenTree]>) -> Vec<String> {
+        flatten_token_trees(trees)
+            .iter()
+            .map(|token| format!("{:?} {} {:?}", token.kind, token.span, token.spacing))
+            .collect()
+    }
+
+    #[test]
+    fn to_proc_macro2() {
+        let stream = to_token_stream(&trees(SRC)).unwrap();
+        let reference = pm::TokenStream::from_str(SRC).unwrap();
+        assert_eq!(stream.to_string(), reference.to_string());
+    }
+
+    #[test]
+    fn from_proc_macro2() {
+        let stream = pm::TokenStream::from_str(SRC).unwrap();
+        let converted = from_token_stream(stream, FileId::default(), Edition::default()).unwrap();
+        assert_eq!(describe(converted), describe(trees(SRC)));
+    }
+
+    #[test]
+    fn spacing() {
+        let stream = to_token_stream(&trees("a += - > b")).unwrap();
+        let puncts: Vec<_> = stream
+            .into_iter()
+            .filter_map(|tree| match tree {
+                pm::TokenTree::Punct(punct) => Some((punct.as_char(), punct.spacing())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            puncts,
+            [
+                ('+', pm::Spacing::Joint),
+                ('=', pm::Spacing::Alone),
+                ('-', pm::Spacing::Alone),
+                ('>', pm::Spacing::Alone),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let stream = to_token_stream(&trees(SRC)).unwrap();
+        let back = from_token_stream(stream.clone(), FileId::default(), Edition::default());
+        let again = to_token_stream(&back.unwrap()).unwrap();
+        assert_eq!(again.to_string(), stream.to_string());
+    }
+
+    #[test]
+    fn errors() {
+        let tokens = match tokenize(
+            SrcIterator::new("a § b"),
+            FileId::default(),
+            &TokenizeOptions::default(),
+        ) {
+            Ok(tokens) => tokens,
+            Err(error) => error.tokens,
+        };
+        let trees = build_token_trees(tokens).unwrap();
+        let Err(ConversionError::Lex(error)) = to_token_stream(&trees) else {
+            panic!("the stray character converted");
+        };
+        assert_eq!(error.kind, LexErrorKind::StrayCharacter('§'));
+        assert_eq!(error.span.byte_range(), 2..4);
+
+        // proc_macro2 takes negative literals, the lexer splits off the minus
+        let stream = pm::TokenStream::from(pm::TokenTree::from(pm::Literal::i32_suffixed(-1)));
+        let converted = from_token_stream(stream, FileId::default(), Edition::default()).unwrap();
+        let kinds: Vec<_> = flatten_token_trees(converted)
+            .iter()
+            .map(|token| format!("{:?}", token.kind))
+            .collect();
+        assert_eq!(kinds.len(), 2);
+        assert!(kinds[0].starts_with("Punctuation(Minus)"), "{kinds:?}");
+        assert!(kinds[1].starts_with("IntegerLiteral"), "{kinds:?}");
+    }
+}
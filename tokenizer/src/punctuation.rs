@@ -1,7 +1,15 @@
-use tokenizer_trait::SrcIterator;
+use tokenizer_trait::{SrcIterator, Token};
 
+/// Whether a punctuation token is immediately followed by another punctuation token, the same as
+/// `proc_macro::Spacing`. `Joint` tokens can combine with the next one into a compound operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Spacing {
+    Joint,
+    #[default]
+    Alone,
+}
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Punctuation {
     SingleEqual,
     Less,
@@ -57,86 +65,246 @@ pub enum Punctuation {
     RightParen,
 }
 
-impl tokenizer_trait::Token for Punctuation {
+impl Token for Punctuation {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
-        //first try match len 3
-        let mut data_3_taken = data.clone();
-        (0..3).for_each(|_| {
-            data_3_taken.next();
-        });
-        let first_three = data.clone().take(3).collect::<String>();
-        match first_three.as_str() {
-            "<<=" => return Some((Self::DoubleLessEqual, data_3_taken)),
-            ">>=" => return Some((Self::DoubleGreaterEqual, data_3_taken)),
-            "..." => return Some((Self::TripleDot, data_3_taken)),
-            "..=" => return Some((Self::DoubleDotEqual, data_3_taken)),
-            _ => {}
+        // longest match first
+        let (punctuation, len) = match data.rest().as_bytes() {
+            [b'<', b'<', b'=', ..] => (Self::DoubleLessEqual, 3),
+            [b'>', b'>', b'=', ..] => (Self::DoubleGreaterEqual, 3),
+            [b'.', b'.', b'.', ..] => (Self::TripleDot, 3),
+            [b'.', b'.', b'=', ..] => (Self::DoubleDotEqual, 3),
+            [b'<', b'=', ..] => (Self::LessEqual, 2),
+            [b'=', b'=', ..] => (Self::DobuleEqual, 2),
+            [b'!', b'=', ..] => (Self::NotEqual, 2),
+            [b'>', b'=', ..] => (Self::GreaterEqual, 2),
+            [b'&', b'&', ..] => (Self::DobuleAnd, 2),
+            [b'|', b'|', ..] => (Self::DobuleOr, 2),
+            [b'<', b'<', ..] => (Self::DoubleLess, 2),
+            [b'>', b'>', ..] => (Self::DoubleGreater, 2),
+            [b'+', b'=', ..] => (Self::PlusEqual, 2),
+            [b'-', b'=', ..] => (Self::MinusEqual, 2),
+            [b'*', b'=', ..] => (Self::StarEqual, 2),
+            [b'/', b'=', ..] => (Self::SlashEqual, 2),
+            [b'%', b'=', ..] => (Self::PercentEqual, 2),
+            [b'^', b'=', ..] => (Self::CaretEqual, 2),
+            [b'&', b'=', ..] => (Self::SingleAndEqual, 2),
+            [b'|', b'=', ..] => (Self::SingleOrEqual, 2),
+            [b'.', b'.', ..] => (Self::DoubleDot, 2),
+            [b':', b':', ..] => (Self::DoubleColon, 2),
+            [b'-', b'>', ..] => (Self::DashGreater, 2),
+            [b'<', b'-', ..] => (Self::LessDash, 2),
+            [b'=', b'>', ..] => (Self::EqualGreater, 2),
+            [b'=', ..] => (Self::SingleEqual, 1),
+            [b'<', ..] => (Self::Less, 1),
+            [b'>', ..] => (Self::Greater, 1),
+            [b'!', ..] => (Self::Bang, 1),
+            [b'~', ..] => (Self::Tilde, 1),
+            [b'+', ..] => (Self::Plus, 1),
+            [b'-', ..] => (Self::Minus, 1),
+            [b'*', ..] => (Self::Star, 1),
+            [b'/', ..] => (Self::Slash, 1),
+            [b'%', ..] => (Self::Percent, 1),
+            [b'^', ..] => (Self::Caret, 1),
+            [b'&', ..] => (Self::SingleAnd, 1),
+            [b'|', ..] => (Self::SingleOr, 1),
+            [b'@', ..] => (Self::At, 1),
+            [b'.', ..] => (Self::SingleDot, 1),
+            [b',', ..] => (Self::Comma, 1),
+            [b';', ..] => (Self::Semicolon, 1),
+            [b':', ..] => (Self::Colon, 1),
+            [b'#', ..] => (Self::Hash, 1),
+            [b'$', ..] => (Self::Dollar, 1),
+            [b'?', ..] => (Self::Question, 1),
+            [b'{', ..] => (Self::LeftCurly, 1),
+            [b'}', ..] => (Self::RightCurly, 1),
+            [b'[', ..] => (Self::LeftSquare, 1),
+            [b']', ..] => (Self::RightSquare, 1),
+            [b'(', ..] => (Self::LeftParen, 1),
+            [b')', ..] => (Self::RightParen, 1),
+            _ => return None,
+        };
+        data.bump(len);
+        Some((punctuation, data))
+    }
+}
+
+impl Punctuation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DoubleLessEqual => "<<=",
+            Self::DoubleGreaterEqual => ">>=",
+            Self::TripleDot => "...",
+            Self::DoubleDotEqual => "..=",
+            Self::LessEqual => "<=",
+            Self::DobuleEqual => "==",
+            Self::NotEqual => "!=",
+            Self::GreaterEqual => ">=",
+            Self::DobuleAnd => "&&",
+            Self::DobuleOr => "||",
+            Self::DoubleLess => "<<",
+            Self::DoubleGreater => ">>",
+            Self::PlusEqual => "+=",
+            Self::MinusEqual => "-=",
+            Self::StarEqual => "*=",
+            Self::SlashEqual => "/=",
+            Self::PercentEqual => "%=",
+            Self::CaretEqual => "^=",
+            Self::SingleAndEqual => "&=",
+            Self::SingleOrEqual => "|=",
+            Self::DoubleDot => "..",
+            Self::DoubleColon => "::",
+            Self::DashGreater => "->",
+            Self::LessDash => "<-",
+            Self::EqualGreater => "=>",
+            Self::SingleEqual => "=",
+            Self::Less => "<",
+            Self::Greater => ">",
+            Self::Bang => "!",
+            Self::Tilde => "~",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Star => "*",
+            Self::Slash => "/",
+            Self::Percent => "%",
+            Self::Caret => "^",
+            Self::SingleAnd => "&",
+            Self::SingleOr => "|",
+            Self::At => "@",
+            Self::SingleDot => ".",
+            Self::Comma => ",",
+            Self::Semicolon => ";",
+            Self::Colon => ":",
+            Self::Hash => "#",
+            Self::Dollar => "$",
+            Self::Question => "?",
+            Self::LeftCurly => "{",
+            Self::RightCurly => "}",
+            Self::LeftSquare => "[",
+            Self::RightSquare => "]",
+            Self::LeftParen => "(",
+            Self::RightParen => ")",
+        }
+    }
+
+    /// Brackets, braces and parentheses. They delimit groups and are never joint.
+    pub fn is_delimiter(&self) -> bool {
+        matches!(
+            self,
+            Self::LeftCurly
+                | Self::RightCurly
+                | Self::LeftSquare
+                | Self::RightSquare
+                | Self::LeftParen
+                | Self::RightParen
+        )
+    }
+
+    /// Splits the first char off a compound punctuation, like `>>=` into `>` and `>=`. The parser
+    /// needs this where the lexer glued together tokens that belong to different rules, such as
+    /// the `>>` closing `Vec<Vec<u8>>` or the `||` of a closure without arguments. `None` for
+    /// single char punctuation, and for `..=` since `.=` is no token.
+    pub fn split_first(&self) -> Option<(Self, Self)> {
+        let text = self.as_str();
+        if text.len() == 1 {
+            return None;
         }
-        //then try match len 2
-        let mut data_2_taken = data.clone();
-        (0..2).for_each(|_| {
-            data_2_taken.next();
-        });
-        let first_two = data.clone().take(2).collect::<String>();
-        match first_two.as_str() {
-            "<=" => return Some((Self::LessEqual, data_2_taken)),
-            "==" => return Some((Self::DobuleEqual, data_2_taken)),
-            "!=" => return Some((Self::NotEqual, data_2_taken)),
-            ">=" => return Some((Self::GreaterEqual, data_2_taken)),
-            "&&" => return Some((Self::DobuleAnd, data_2_taken)),
-            "||" => return Some((Self::DobuleOr, data_2_taken)),
-            "<<" => return Some((Self::DoubleLess, data_2_taken)),
-            ">>" => return Some((Self::DoubleGreater, data_2_taken)),
-            "+=" => return Some((Self::PlusEqual, data_2_taken)),
-            "-=" => return Some((Self::MinusEqual, data_2_taken)),
-            "*=" => return Some((Self::StarEqual, data_2_taken)),
-            "/=" => return Some((Self::SlashEqual, data_2_taken)),
-            "%=" => return Some((Self::PercentEqual, data_2_taken)),
-            "^=" => return Some((Self::CaretEqual, data_2_taken)),
-            "&=" => return Some((Self::SingleAndEqual, data_2_taken)),
-            "|=" => return Some((Self::SingleOrEqual, data_2_taken)),
-            ".." => return Some((Self::DoubleDot, data_2_taken)),
-            "::" => return Some((Self::DoubleColon, data_2_taken)),
-            "->" => return Some((Self::DashGreater, data_2_taken)),
-            "<-" => return Some((Self::LessDash, data_2_taken)),
-            "=>" => return Some((Self::EqualGreater, data_2_taken)),
-            _ => {}
+        let (first, _) = Self::parse_token(SrcIterator::new(&text[..1]))?;
+        let (rest, after) = Self::parse_token(SrcIterator::new(&text[1..]))?;
+        // the rest has to be a single token, or part of it would get lost
+        if !after.rest().is_empty() {
+            return None;
         }
-        //then try match len 1
-        let mut data_1_taken = data.clone();
-        data_1_taken.next();
-        let first_char = *data.peek()?;
-        match first_char {
-            '=' => return Some((Self::SingleEqual, data_1_taken)),
-            '<' => return Some((Self::Less, data_1_taken)),
-            '>' => return Some((Self::Greater, data_1_taken)),
-            '!' => return Some((Self::Bang, data_1_taken)),
-            '~' => return Some((Self::Tilde, data_1_taken)),
-            '+' => return Some((Self::Plus, data_1_taken)),
-            '-' => return Some((Self::Minus, data_1_taken)),
-            '*' => return Some((Self::Star, data_1_taken)),
-            '/' => return Some((Self::Slash, data_1_taken)),
-            '%' => return Some((Self::Percent, data_1_taken)),
-            '^' => return Some((Self::Caret, data_1_taken)),
-            '&' => return Some((Self::SingleAnd, data_1_taken)),
-            '|' => return Some((Self::SingleOr, data_1_taken)),
-            '@' => return Some((Self::At, data_1_taken)),
-            '.' => return Some((Self::SingleDot, data_1_taken)),
-            ',' => return Some((Self::Comma, data_1_taken)),
-            ';' => return Some((Self::Semicolon, data_1_taken)),
-            ':' => return Some((Self::Colon, data_1_taken)),
-            '#' => return Some((Self::Hash, data_1_taken)),
-            '$' => return Some((Self::Dollar, data_1_taken)),
-            '?' => return Some((Self::Question, data_1_taken)),
-            '{' => return Some((Self::LeftCurly, data_1_taken)),
-            '}' => return Some((Self::RightCurly, data_1_taken)),
-            '[' => return Some((Self::LeftSquare, data_1_taken)),
-            ']' => return Some((Self::RightSquare, data_1_taken)),
-            '(' => return Some((Self::LeftParen, data_1_taken)),
-            ')' => return Some((Self::RightParen, data_1_taken)),
-            _ => {}
+        Some((first, rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TokenKind, TokenizeOptions, span::FileId, tokenize};
+
+    #[test]
+    fn split_first() {
+        use Punctuation::*;
+        let splits = [
+            (DoubleLessEqual, Less, LessEqual),
+            (DoubleGreaterEqual, Greater, GreaterEqual),
+            (TripleDot, SingleDot, DoubleDot),
+            (LessEqual, Less, SingleEqual),
+            (DobuleEqual, SingleEqual, SingleEqual),
+            (NotEqual, Bang, SingleEqual),
+            (GreaterEqual, Greater, SingleEqual),
+            (DobuleAnd, SingleAnd, SingleAnd),
+            (DobuleOr, SingleOr, SingleOr),
+            (DoubleLess, Less, Less),
+            (DoubleGreater, Greater, Greater),
+            (PlusEqual, Plus, SingleEqual),
+            (MinusEqual, Minus, SingleEqual),
+            (StarEqual, Star, SingleEqual),
+            (SlashEqual, Slash, SingleEqual),
+            (PercentEqual, Percent, SingleEqual),
+            (CaretEqual, Caret, SingleEqual),
+            (SingleAndEqual, SingleAnd, SingleEqual),
+            (SingleOrEqual, SingleOr, SingleEqual),
+            (DoubleDot, SingleDot, SingleDot),
+            (DoubleColon, Colon, Colon),
+            (DashGreater, Minus, Greater),
+            (LessDash, Less, Minus),
+            (EqualGreater, SingleEqual, Greater),
+        ];
+        for (punctuation, first, rest) in splits {
+            assert_eq!(
+                punctuation.split_first(),
+                Some((first, rest)),
+                "{punctuation:?}"
+            );
         }
+        assert_eq!(DoubleDotEqual.split_first(), None);
+        assert_eq!(Hash.split_first(), None);
+    }
 
-        None
+    #[test]
+    fn split_punctuation() {
+        let src = "a <<= ..= ... >>=\n  :: -> ||";
+        let tokens = tokenize(
+            SrcIterator::new(src),
+            FileId::default(),
+            &TokenizeOptions::default(),
+        )
+        .unwrap();
+        let splits: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| {
+                let (first, rest) = token.split_punctuation()?;
+                let kind = |token: &crate::Token| match token.kind {
+                    TokenKind::Punctuation(punctuation) => punctuation.as_str(),
+                    _ => panic!("expected punctuation, got {:?}", token.kind),
+                };
+                assert_eq!(first.span.start, token.span.start);
+                assert_eq!(first.span.end, rest.span.start);
+                assert_eq!(rest.span.end, token.span.end);
+                assert_eq!(&src[first.span.byte_range()], kind(&first));
+                assert_eq!(&src[rest.span.byte_range()], kind(&rest));
+                assert_eq!(first.spacing, Spacing::Joint);
+                assert_eq!(rest.spacing, token.spacing);
+                Some((
+                    kind(&first),
+                    kind(&rest),
+                    first.span.start.line,
+                    rest.span.start.column,
+                ))
+            })
+            .collect();
+        assert_eq!(
+            splits,
+            [
+                ("<", "<=", 1, 4),
+                (".", "..", 1, 12),
+                (">", ">=", 1, 16),
+                (":", ":", 2, 4),
+                ("-", ">", 2, 7),
+                ("|", "|", 2, 10),
+            ]
+        );
     }
 }
@@ -5,12 +5,35 @@ use crate::suffix::Suffix;
 
 #[derive(Debug)]
 pub struct RawByteStringLiteral {
-    value: Box<[u8]>,
+    source: Box<str>,
+    hashes: u8,
     suffix: Option<Suffix>
 }
 
+impl RawByteStringLiteral {
+    pub fn value(&self) -> &[u8] {
+        let hashes = self.hashes as usize;
+        self.source[3 + hashes..self.source.len() - 1 - hashes].as_bytes()
+    }
+
+    /// The literal as written, `br`, hashes and quotes included and suffix excluded
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Number of `#`s around the quotes
+    pub fn hashes(&self) -> u8 {
+        self.hashes
+    }
+
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+}
+
 impl tokenizer_trait::Token for RawByteStringLiteral {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let start = data.offset();
         if data.next()? != 'b' || data.next()? != 'r' {
             return None;
         }
@@ -28,31 +51,30 @@ impl tokenizer_trait::Token for RawByteStringLiteral {
             return None;
         }
 
-        let mut parsed = Vec::new();
-
         while let Some(chr) = data.next() {
             if chr == '\r' {
                 return None;
             }
             if chr == '"' {
-                let mut temp_peekable = data.clone();
+                let mut temp_peekable = data;
                 let mut num_after_hashes = 0;
-                while let Some('#') = temp_peekable.peek() {
+                while let Some('#') = temp_peekable.peek() && num_after_hashes < num_hashes {
                     num_after_hashes += 1;
                     temp_peekable.next();
                 }
                 if num_after_hashes == num_hashes {
                     //found the end of the string literal
-                    
-                    if let Some(suffix) = Suffix::parse_token(temp_peekable.clone()) {
+                    let source = temp_peekable.slice_from(start).into();
+                    let hashes = num_hashes as u8;
+                    if let Some(suffix) = Suffix::parse_token(temp_peekable) {
                         return Some((
-                            Self { value: parsed.into_boxed_slice(), suffix: Some(suffix.0) },
+                            Self { source, hashes, suffix: Some(suffix.0) },
                             suffix.1,
                         ));
                     }
                     
                     return Some((
-                        Self { value: parsed.into_boxed_slice(), suffix: None },
+                        Self { source, hashes, suffix: None },
                         temp_peekable,
                     ));
                 }
@@ -60,7 +82,6 @@ impl tokenizer_trait::Token for RawByteStringLiteral {
             if !chr.is_ascii() {
                 return None;
             }
-            parsed.push(chr as u8);
         }
 
 
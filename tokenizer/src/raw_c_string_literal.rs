@@ -1,4 +1,6 @@
-use tokenizer_trait::SrcIterator;
+use tokenizer_trait::{Edition, SrcIterator};
+
+use std::ffi::{CStr, CString};
 
 use crate::suffix::Suffix;
 
@@ -6,12 +8,44 @@ use crate::suffix::Suffix;
 
 #[derive(Debug)]
 pub struct RawCStringLiteral {
-    value: Box<[u8]>,
+    value: CString,
+    source: Box<str>,
+    hashes: u8,
     suffix: Option<Suffix>
 }
 
+impl RawCStringLiteral {
+    pub fn value(&self) -> &CStr {
+        &self.value
+    }
+
+    /// The bytes including the trailing NUL, as they would be laid out in memory
+    pub fn bytes_with_nul(&self) -> &[u8] {
+        self.value.to_bytes_with_nul()
+    }
+
+    /// The literal as written, `cr`, hashes and quotes included and suffix excluded
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Number of `#`s around the quotes
+    pub fn hashes(&self) -> u8 {
+        self.hashes
+    }
+
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+}
+
 impl tokenizer_trait::Token for RawCStringLiteral {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        // C strings exist since 2021
+        if data.edition() < Edition::Edition2021 {
+            return None;
+        }
+        let start = data.offset();
         if data.next()? != 'c' || data.next()? != 'r' {
             return None;
         }
@@ -36,32 +70,79 @@ impl tokenizer_trait::Token for RawCStringLiteral {
                 return None;
             }
             if chr == '"' {
-                let mut temp_peekable = data.clone();
+                let mut temp_peekable = data;
                 let mut num_after_hashes = 0;
-                while let Some('#') = temp_peekable.peek() {
+                while let Some('#') = temp_peekable.peek() && num_after_hashes < num_hashes {
                     num_after_hashes += 1;
                     temp_peekable.next();
                 }
                 if num_after_hashes == num_hashes {
                     //found the end of the string literal
-                    
-                    if let Some(suffix) = Suffix::parse_token(temp_peekable.clone()) {
+                    let source = temp_peekable.slice_from(start).into();
+                    let hashes = num_hashes as u8;
+                    let value = CString::new(parsed).ok()?;
+                    if let Some(suffix) = Suffix::parse_token(temp_peekable) {
                         return Some((
-                            Self { value: parsed.into_boxed_slice(), suffix: Some(suffix.0) },
+                            Self { value, source, hashes, suffix: Some(suffix.0) },
                             suffix.1,
                         ));
                     }
                     
                     return Some((
-                        Self { value: parsed.into_boxed_slice(), suffix: None },
+                        Self { value, source, hashes, suffix: None },
                         temp_peekable,
                     ));
                 }
             }
-            parsed.extend_from_slice(&chr.to_string().into_bytes());
+            parsed.extend_from_slice(chr.encode_utf8(&mut [0; 4]).as_bytes());
         }
 
 
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokenizer_trait::Token;
+
+    use super::*;
+    use crate::{TokenizeOptions, lex_error::LexErrorKind, span::FileId, tokenize};
+
+    /// `src` lexed as a single raw C string literal
+    fn raw_c_string(src: &str) -> RawCStringLiteral {
+        let (literal, rest) = RawCStringLiteral::parse_token(SrcIterator::new(src)).unwrap();
+        assert_eq!(rest.rest(), "", "{src:?} isn't a single literal");
+        literal
+    }
+
+    #[test]
+    fn escapes_are_kept() {
+        let literal = raw_c_string(r#"cr"\x80\u{0}""#);
+        assert_eq!(literal.value().to_bytes(), br"\x80\u{0}");
+        assert_eq!(literal.bytes_with_nul(), b"\\x80\\u{0}\0");
+    }
+
+    #[test]
+    fn hashes() {
+        let literal = raw_c_string(r##"cr#"a"b é"#"##);
+        assert_eq!(literal.hashes(), 1);
+        assert_eq!(literal.value().to_bytes(), "a\"b é".as_bytes());
+        assert_eq!(literal.bytes_with_nul(), "a\"b é\0".as_bytes());
+        assert_eq!(raw_c_string(r#"cr"""#).bytes_with_nul(), b"\0");
+    }
+
+    #[test]
+    fn nul_is_rejected() {
+        let src = "x cr#\"a\0b\"# y";
+        assert!(RawCStringLiteral::parse_token(SrcIterator::new(&src[2..])).is_none());
+        let options = TokenizeOptions::default();
+        let error = tokenize(SrcIterator::new(src), FileId::default(), &options).unwrap_err();
+        let errors: Vec<_> = error
+            .errors
+            .iter()
+            .map(|error| (error.kind, error.span.byte_range()))
+            .collect();
+        assert_eq!(errors, [(LexErrorKind::NulInCString, 7..8)]);
+    }
+}
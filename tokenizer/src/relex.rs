@@ -0,0 +1,188 @@
+use std::ops::Range;
+
+use crate::{
+    Lexer, Token, TokenKind, TokenizeOptions,
+    lex_error::LexError,
+    line_endings::NormalizedSrc,
+    span::{FileId, Pos, Span},
+};
+
+/// Replaces `range` of a source text with `replacement`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// `src` with the edit made
+    pub fn apply(&self, src: &str) -> String {
+        let mut out = String::with_capacity(src.len() - self.range.len() + self.replacement.len());
+        out.push_str(&src[..self.range.start]);
+        out.push_str(&self.replacement);
+        out.push_str(&src[self.range.end..]);
+        out
+    }
+}
+
+#[derive(Debug)]
+pub struct Relexed {
+    pub tokens: Box<[Token]>,
+    /// Indices into `tokens` that may differ from the old stream. Everything before is the old
+    /// tokens as they were, everything after is the old tokens moved by the edit.
+    pub changed: Range<usize>,
+    /// Every error in the new text, the same as [`crate::tokenize`] would find. The old errors
+    /// outside of `changed` are carried over, moved along with their tokens.
+    pub errors: Box<[LexError]>,
+}
+
+/// Updates `tokens` and `errors`, which [`crate::tokenize`] gave for some text with `options`,
+/// for `edit` on that text. `src` is the text after the edit.
+///
+/// Lexing starts a token before the edit, and further back over tokens with nothing in between,
+/// since where a token ends can depend on what follows it. It stops at the first token after the
+/// edit that starts where one of the old tokens did: from there on the lexer sees the same text
+/// as before, so the rest of the old tokens are kept with their spans moved. An edit that opens a
+/// block comment or a raw string, or closes one early, keeps lexing until the old tokens line up
+/// again, which may be the end of the file.
+pub fn relex(
+    mut tokens: Vec<Token>,
+    mut errors: Vec<LexError>,
+    src: &str,
+    edit: &TextEdit,
+    file: FileId,
+    options: &TokenizeOptions,
+) -> Relexed {
+    let normalized = NormalizedSrc::new(src);
+    let lexer = Lexer::new(&normalized, src, 0, file, options);
+    // an insertion right at the end of a token can still extend it
+    let touched = tokens.partition_point(|token| token.span.end.offset < edit.range.start);
+    let mut restart = touched.saturating_sub(1);
+    // where a token ends can depend on the text right after it, like `'r#a` only being a raw
+    // lifetime if no `'` follows, so everything glued to the edit is lexed again
+    while restart > 0 && tokens[restart - 1].span.end.offset == tokens[restart].span.start.offset {
+        restart -= 1;
+    }
+    // telling a shebang from an inner attribute can look past the edit, and frontmatter only
+    // comes before other tokens, so close to the start of the file it's easiest to start over
+    if tokens[..restart].iter().all(lexed_at_start)
+        || lexer
+            .shebang_lookahead_end()
+            .is_some_and(|end| end >= edit.range.start)
+    {
+        restart = 0;
+    }
+    // the old tokens the lexer can line up with, the text from their start on is untouched. The
+    // end of file token never lines up, the lexer makes a new one when it gets that far.
+    let old = tokens.split_off(restart);
+    // errors are always within the token they were found in
+    let kept_end = tokens.last().map_or(0, |token| token.span.end.offset);
+    let old_errors =
+        errors.split_off(errors.partition_point(|error| error.span.start.offset < kept_end));
+    let mut old = old
+        .into_iter()
+        .skip_while(|token| token.span.start.offset < edit.range.end);
+    let shift = edit.replacement.len() as isize - edit.range.len() as isize;
+
+    let mut lexer = match tokens.is_empty() {
+        true => lexer,
+        false => Lexer::resume(&normalized, src, tokens, file, options),
+    };
+    let changed_start = restart.saturating_sub(1);
+    let mut next_old = old.next();
+    while let Some(pos) = lexer.at_token() {
+        while let Some(token) = &next_old
+            && (token.span.start.offset as isize + shift) < pos.offset as isize
+        {
+            next_old = old.next();
+        }
+        if let Some(token) = &next_old
+            && (token.span.start.offset as isize + shift) == pos.offset as isize
+            && lexer.past_start()
+            && !lexed_at_start(token)
+        {
+            let moved = token.span.start;
+            let mut first = next_old.unwrap();
+            if let Some(lossless) = &mut first.lossless {
+                // comes before `moved` and gets lexed again anyway
+                lossless.leading = Box::new([]);
+            }
+            let rest = std::iter::once(first).chain(old);
+            let rest: Vec<Token> = rest.map(|token| move_token(token, moved, pos)).collect();
+            let kept = rest.len();
+            lexer.resync(rest);
+            let (tokens, new_errors) = lexer.into_parts();
+            errors.extend(new_errors);
+            errors.extend(
+                old_errors
+                    .into_iter()
+                    .filter(|error| error.span.start >= moved)
+                    .map(|error| LexError {
+                        span: move_span(error.span, moved, pos),
+                        ..error
+                    }),
+            );
+            // the first kept token has its leading trivia worked out again
+            let changed_end = tokens.len() - kept + usize::from(options.lossless);
+            return Relexed {
+                changed: changed_start..changed_end,
+                tokens: tokens.into_boxed_slice(),
+                errors: errors.into_boxed_slice(),
+            };
+        }
+        if let Some(token) = lexer.lex() {
+            lexer.push(token);
+        }
+    }
+    let (tokens, new_errors) = lexer.finish();
+    errors.extend(new_errors);
+    Relexed {
+        changed: changed_start..tokens.len(),
+        tokens: tokens.into_boxed_slice(),
+        errors: errors.into_boxed_slice(),
+    }
+}
+
+/// Moves `token`, which comes at or after `from` in the old text, along with `from` going to `to`
+fn move_token(mut token: Token, from: Pos, to: Pos) -> Token {
+    token.span = move_span(token.span, from, to);
+    if let Some(lossless) = &mut token.lossless {
+        for trivia in lossless
+            .leading
+            .iter_mut()
+            .chain(lossless.trailing.iter_mut())
+        {
+            trivia.span = move_span(trivia.span, from, to);
+        }
+    }
+    token
+}
+
+fn move_span(span: Span, from: Pos, to: Pos) -> Span {
+    let move_pos = |pos: Pos| Pos {
+        offset: pos.offset - from.offset + to.offset,
+        line: pos.line - from.line + to.line,
+        // only what's on the same line moves sideways
+        column: match pos.line == from.line {
+            true => pos.column - from.column + to.column,
+            false => pos.column,
+        },
+    };
+    Span::new(span.file, move_pos(span.start), move_pos(span.end))
+}
+
+/// Tokens that may only be what they are because the lexer was where the shebang and frontmatter
+/// can be. Error tokens are counted in since they can be broken frontmatter.
+fn lexed_at_start(token: &Token) -> bool {
+    matches!(
+        token.kind,
+        TokenKind::Shebang(_) | TokenKind::Frontmatter(_) | TokenKind::Error(_)
+    )
+}
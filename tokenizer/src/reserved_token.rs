@@ -1,3 +1,5 @@
+use tokenizer_trait::Edition;
+
 use crate::{
     IdentifierOrKeyword, StringLiteral,
     identifier_or_keyword::ReservedRawIdentifier,
@@ -25,6 +27,10 @@ impl tokenizer_trait::Token for ReservedGuardedStringLiteral {
     fn parse_token(
         mut data: tokenizer_trait::SrcIterator,
     ) -> Option<(Self, tokenizer_trait::SrcIterator)> {
+        // reserved since 2024
+        if data.edition() < Edition::Edition2024 {
+            return None;
+        }
         if data.next()? != '#' {
             return None;
         }
@@ -44,7 +50,7 @@ impl tokenizer_trait::Token for ReservedNumber {
         data: tokenizer_trait::SrcIterator,
     ) -> Option<(Self, tokenizer_trait::SrcIterator)> {
         'block: {
-            let temp_data = data.clone();
+            let temp_data = data;
             let Some((_, mut temp_data)) = BinLiteral::parse_token(temp_data) else {
                 break 'block;
             };
@@ -56,7 +62,7 @@ impl tokenizer_trait::Token for ReservedNumber {
             }
         }
         'block: {
-            let temp_data = data.clone();
+            let temp_data = data;
             let Some((_, mut temp_data)) = OctLiteral::parse_token(temp_data) else {
                 break 'block;
             };
@@ -68,7 +74,7 @@ impl tokenizer_trait::Token for ReservedNumber {
             }
         }
         'block: {
-            let temp_data = data.clone();
+            let temp_data = data;
             let Some((_, mut temp_data)) = BinLiteral::parse_token(temp_data) else {
                 break 'block;
             };
@@ -78,13 +84,13 @@ impl tokenizer_trait::Token for ReservedNumber {
             let Some(after) = temp_data.peek() else {
                 break 'block;
             };
-            if *after == '.' || *after == '_' || unicode_ident::is_xid_start(*after) {
+            if after == '.' || after == '_' || unicode_ident::is_xid_start(after) {
                 break 'block;
             }
             return Some((Self, temp_data));
         }
         'block: {
-            let temp_data = data.clone();
+            let temp_data = data;
             let Some((_, mut temp_data)) = OctLiteral::parse_token(temp_data) else {
                 break 'block;
             };
@@ -94,13 +100,13 @@ impl tokenizer_trait::Token for ReservedNumber {
             let Some(after) = temp_data.peek() else {
                 break 'block;
             };
-            if *after == '.' || *after == '_' || unicode_ident::is_xid_start(*after) {
+            if after == '.' || after == '_' || unicode_ident::is_xid_start(after) {
                 break 'block;
             }
             return Some((Self, temp_data));
         }
         'block: {
-            let temp_data = data.clone();
+            let temp_data = data;
             let Some((_, mut temp_data)) = HexLiteral::parse_token(temp_data) else {
                 break 'block;
             };
@@ -110,13 +116,13 @@ impl tokenizer_trait::Token for ReservedNumber {
             let Some(after) = temp_data.peek() else {
                 break 'block;
             };
-            if *after == '.' || *after == '_' || unicode_ident::is_xid_start(*after) {
+            if after == '.' || after == '_' || unicode_ident::is_xid_start(after) {
                 break 'block;
             }
             return Some((Self, temp_data));
         }
         'block: {
-            let temp_data = data.clone();
+            let temp_data = data;
             let Some((_, mut temp_data)) = BinLiteral::parse_token(temp_data) else {
                 break 'block;
             };
@@ -128,7 +134,7 @@ impl tokenizer_trait::Token for ReservedNumber {
             }
         }
         'block: {
-            let temp_data = data.clone();
+            let temp_data = data;
             let Some((_, mut temp_data)) = OctLiteral::parse_token(temp_data) else {
                 break 'block;
             };
@@ -140,7 +146,7 @@ impl tokenizer_trait::Token for ReservedNumber {
             }
         }
         'block: {
-            let mut temp_data = data.clone();
+            let mut temp_data = data;
             let Some(next) = temp_data.next() else {
                 break 'block;
             };
@@ -170,7 +176,7 @@ impl tokenizer_trait::Token for ReservedNumber {
             }
         }
         'block: {
-            let temp_data = data.clone();
+            let temp_data = data;
             let Some((_, mut temp_data)) = DecLiteral::parse_token(temp_data) else {
                 break 'block;
             };
@@ -188,7 +194,7 @@ impl tokenizer_trait::Token for ReservedNumber {
                 break 'block;
             }
             if let Some(next) = temp_data.peek()
-                && (*next == '+' || *next == '-')
+                && (next == '+' || next == '-')
             {
                 temp_data.next();
             }
@@ -211,12 +217,16 @@ impl tokenizer_trait::Token for ReservedPounds {
     fn parse_token(
         mut data: tokenizer_trait::SrcIterator,
     ) -> Option<(Self, tokenizer_trait::SrcIterator)> {
+        // reserved since 2024
+        if data.edition() < Edition::Edition2024 {
+            return None;
+        }
         if data.next()? != '#' {
             return None;
         }
         let next = data.peek()?;
-        if *next == '#' {
-            while let Some(&'#') = data.peek() {
+        if next == '#' {
+            while let Some('#') = data.peek() {
                 data.next();
             }
             return Some((Self, data));
@@ -232,7 +242,11 @@ impl tokenizer_trait::Token for ReservedTokenDoubleQuote {
     fn parse_token(
         data: tokenizer_trait::SrcIterator,
     ) -> Option<(Self, tokenizer_trait::SrcIterator)> {
-        let mut inner = IdentifierOrKeyword::parse_token(data.clone())?;
+        // prefixes are reserved since 2021
+        if data.edition() < Edition::Edition2021 {
+            return None;
+        }
+        let mut inner = IdentifierOrKeyword::parse_token(data)?;
         let parsed = inner.0.parsed();
         if ["b", "c", "r", "br", "cr"].contains(&parsed) {
             return None;
@@ -252,6 +266,10 @@ impl tokenizer_trait::Token for ReservedTokenLifetime {
     fn parse_token(
         mut data: tokenizer_trait::SrcIterator,
     ) -> Option<(Self, tokenizer_trait::SrcIterator)> {
+        // prefixes are reserved since 2021
+        if data.edition() < Edition::Edition2021 {
+            return None;
+        }
         if data.next()? != '\'' {
             return None;
         }
@@ -274,7 +292,11 @@ impl tokenizer_trait::Token for ReservedTokenPound {
     fn parse_token(
         data: tokenizer_trait::SrcIterator,
     ) -> Option<(Self, tokenizer_trait::SrcIterator)> {
-        let mut inner = IdentifierOrKeyword::parse_token(data.clone())?;
+        // prefixes are reserved since 2021
+        if data.edition() < Edition::Edition2021 {
+            return None;
+        }
+        let mut inner = IdentifierOrKeyword::parse_token(data)?;
         let parsed = inner.0.parsed();
         if ["r", "br", "cr"].contains(&parsed) {
             return None;
@@ -294,6 +316,10 @@ impl tokenizer_trait::Token for ReservedTokenSingleQuote {
     fn parse_token(
         data: tokenizer_trait::SrcIterator,
     ) -> Option<(Self, tokenizer_trait::SrcIterator)> {
+        // prefixes are reserved since 2021
+        if data.edition() < Edition::Edition2021 {
+            return None;
+        }
         let mut inner = IdentifierOrKeyword::parse_token(data)?;
         if inner.0.parsed() == "b" {
             return None;
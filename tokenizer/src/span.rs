@@ -0,0 +1,210 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// A location in the source text. `offset` is a byte offset, `line` and `column` are 1-based,
+/// with `column` counted in chars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    pub offset: usize,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Default for Pos {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+/// Turns byte offsets into [`Pos`]es. Only has to look at the text in between when the offsets
+/// are asked for in increasing order, which is how the lexer goes through a file.
+#[derive(Debug, Clone)]
+pub struct PosTracker<'a> {
+    src: &'a str,
+    last: Pos,
+}
+
+impl<'a> PosTracker<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            last: Pos::default(),
+        }
+    }
+
+    /// Tracker that has already counted its way up to `pos`, for starting in the middle of `src`
+    pub fn resume(src: &'a str, pos: Pos) -> Self {
+        Self { src, last: pos }
+    }
+
+    pub fn pos(&mut self, offset: usize) -> Pos {
+        if offset < self.last.offset {
+            self.last = Pos::default();
+        }
+        let between = &self.src[self.last.offset..offset];
+        match between.rfind('\n') {
+            Some(last_newline) => {
+                self.last.line += between.bytes().filter(|byte| *byte == b'\n').count() as u32;
+                self.last.column = 1 + between[last_newline + 1..].chars().count() as u32;
+            }
+            None => self.last.column += between.chars().count() as u32,
+        }
+        self.last.offset = offset;
+        self.last
+    }
+
+    pub fn span(&mut self, file: FileId, range: std::ops::Range<usize>) -> Span {
+        Span::new(file, self.pos(range.start), self.pos(range.end))
+    }
+}
+
+static FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Identifies a source file registered with [`FileId::register`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct FileId(u32);
+
+impl FileId {
+    pub fn register(path: &Path) -> Self {
+        let mut files = FILES.lock().unwrap();
+        files.push(path.to_path_buf());
+        Self(files.len() as u32 - 1)
+    }
+
+    /// Path the file was registered with. `None` for [`FileId::default`] if nothing was registered
+    pub fn path(self) -> Option<PathBuf> {
+        FILES.lock().unwrap().get(self.0 as usize).cloned()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub file: FileId,
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    pub fn new(file: FileId, start: Pos, end: Pos) -> Self {
+        Self { file, start, end }
+    }
+
+    /// Span covering both `self` and `other`
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            file: self.file,
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Empty span at the start of `self`
+    pub fn shrink_to_start(self) -> Span {
+        Span {
+            file: self.file,
+            start: self.start,
+            end: self.start,
+        }
+    }
+
+    pub fn byte_range(&self) -> std::ops::Range<usize> {
+        self.start.offset..self.end.offset
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.file.path() {
+            Some(path) => write!(
+                f,
+                "{}:{}:{}",
+                path.display(),
+                self.start.line,
+                self.start.column
+            ),
+            None => write!(f, "{}:{}", self.start.line, self.start.column),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(offset: usize, line: u32, column: u32) -> Pos {
+        Pos {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn lines_and_columns() {
+        let src = "ab\ncd\n\nef";
+        let mut positions = PosTracker::new(src);
+        assert_eq!(positions.pos(0), pos(0, 1, 1));
+        assert_eq!(positions.pos(2), pos(2, 1, 3));
+        assert_eq!(positions.pos(3), pos(3, 2, 1));
+        assert_eq!(positions.pos(7), pos(7, 4, 1));
+        assert_eq!(positions.pos(9), pos(9, 4, 3));
+        // going back starts counting over
+        assert_eq!(positions.pos(4), pos(4, 2, 2));
+    }
+
+    #[test]
+    fn multibyte_columns() {
+        // columns count chars, offsets count bytes
+        let src = "é字😀x\nй";
+        let mut positions = PosTracker::new(src);
+        assert_eq!(positions.pos(2), pos(2, 1, 2));
+        assert_eq!(positions.pos(5), pos(5, 1, 3));
+        assert_eq!(positions.pos(9), pos(9, 1, 4));
+        assert_eq!(positions.pos(10), pos(10, 1, 5));
+        assert_eq!(positions.pos(13), pos(13, 2, 2));
+    }
+
+    #[test]
+    fn resume() {
+        let src = "a\nbé c";
+        let mut positions = PosTracker::resume(src, pos(2, 2, 1));
+        assert_eq!(positions.pos(6), pos(6, 2, 4));
+    }
+
+    #[test]
+    fn span_merging() {
+        let file = FileId::default();
+        let a = Span::new(file, pos(2, 1, 3), pos(4, 1, 5));
+        let b = Span::new(file, pos(8, 2, 1), pos(9, 2, 2));
+        let merged = Span::new(file, pos(2, 1, 3), pos(9, 2, 2));
+        assert_eq!(a.to(b), merged);
+        assert_eq!(b.to(a), merged);
+        // a span inside the other adds nothing
+        assert_eq!(merged.to(b), merged);
+        assert_eq!(a.len(), 2);
+        assert_eq!(merged.byte_range(), 2..9);
+        assert!(a.shrink_to_start().is_empty());
+        assert_eq!(a.shrink_to_start().start, a.start);
+    }
+
+    #[test]
+    fn display() {
+        // not registered, so no path to show
+        let span = Span::new(FileId(u32::MAX), pos(5, 2, 3), pos(6, 2, 4));
+        assert_eq!(span.to_string(), "2:3");
+    }
+}
@@ -6,22 +6,47 @@ use crate::{string_escapes::{ByteEscape, UnicodeEscape}, suffix::Suffix};
 
 #[derive(Debug)]
 pub struct StringLiteral {
-    value: String,
+    /// Only there when the literal has escapes, otherwise the value is the source minus the quotes
+    decoded: Option<Box<str>>,
+    source: Box<str>,
     suffix: Option<Suffix>
 }
 
+impl StringLiteral {
+    /// The string with escapes decoded
+    pub fn value(&self) -> &str {
+        match &self.decoded {
+            Some(decoded) => decoded,
+            None => &self.source[1..self.source.len() - 1],
+        }
+    }
+
+    /// The literal as written, quotes included and suffix excluded
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn suffix(&self) -> Option<&'static str> {
+        self.suffix.as_ref().map(|suffix| suffix.parsed())
+    }
+}
+
 impl tokenizer_trait::Token for StringLiteral {
     fn parse_token(mut data: SrcIterator) -> Option<(Self, SrcIterator)> {
+        let start = data.offset();
         if data.next()? != '"' {
             return None;
         }
 
-        let mut content = String::new();
+        // only copied out of the source once there is an escape to decode
+        let content_start = data.offset();
+        let mut decoded: Option<String> = None;
 
         while let Some(chr) = data.peek() {
-            if *chr == '\\' {
+            if chr == '\\' {
+                let content = decoded.get_or_insert_with(|| data.slice_from(content_start).to_string());
                 //handle escapes
-                if let Some(byte_escape) = ByteEscape::parse_token(data.clone()) {
+                if let Some(byte_escape) = ByteEscape::parse_token(data) {
                     if byte_escape.0.value() >= 0x80 {
                         return None;
                     }
@@ -29,19 +54,19 @@ impl tokenizer_trait::Token for StringLiteral {
                     data = byte_escape.1;
                     continue;
                 }
-                if let Some(unicode_escape) = UnicodeEscape::parse_token(data.clone()) {
+                if let Some(unicode_escape) = UnicodeEscape::parse_token(data) {
                     data = unicode_escape.1;
                     content.push(unicode_escape.0.to_char()?);
 
                     continue;
                 }
                 data.next()?;
-                if *data.peek()? == '\'' {
+                if data.peek()? == '\'' {
                     content.push('\'');
                     data.next();
                     continue;
                 }
-                if *data.peek()? == '"' {
+                if data.peek()? == '"' {
                     content.push('"');
                     data.next();
                     continue;
@@ -50,7 +75,7 @@ impl tokenizer_trait::Token for StringLiteral {
 
                 if data.next()? == '\n' { // \LF string continue
                     while let Some(chr) = data.peek() {
-                        if *chr == ' ' || *chr == '\t' || *chr == '\n' || *chr == '\r' {
+                        if chr == ' ' || chr == '\t' || chr == '\n' || chr == '\r' {
                             data.next();
                         } else {
                             break;
@@ -63,22 +88,26 @@ impl tokenizer_trait::Token for StringLiteral {
             }
 
             let chr = data.next()?;
-            if chr == '\r' || chr == '\0' {
+            if chr == '\r' {
                 return None;
             }
             if chr == '"' {
-                if let Some(suffix) = Suffix::parse_token(data.clone()) {
+                let decoded = decoded.map(String::into_boxed_str);
+                let source = data.slice_from(start).into();
+                if let Some(suffix) = Suffix::parse_token(data) {
                     return Some((
-                        Self { value: content, suffix: Some(suffix.0) },
+                        Self { decoded, source, suffix: Some(suffix.0) },
                         suffix.1,
                     ));
                 }
                 return Some((
-                    Self { value: content, suffix: None },
+                    Self { decoded, source, suffix: None },
                     data,
                 ));
             }
-            content.push(chr);
+            if let Some(decoded) = &mut decoded {
+                decoded.push(chr);
+            }
         }
         None
     }
@@ -1,34 +1,46 @@
 use tokenizer_trait::SrcIterator;
 
-use crate::IdentifierOrKeyword;
-
-
+use crate::{IdentifierOrKeyword, TokenKind, lex_error::LexErrorKind, symbol::kw};
 
 #[derive(Debug)]
 pub struct Suffix {
     parsed: IdentifierOrKeyword,
+    /// Bytes the suffix takes up in the source, which isn't the length of `parsed` when it wasn't
+    /// written in NFC
+    written_len: usize,
 }
 
 impl tokenizer_trait::Token for Suffix {
     fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)> {
         let inner = IdentifierOrKeyword::parse_token(data)?;
-        if inner.0.parsed() == "_" {
+        if inner.0.symbol() == kw::Underscore {
             return None;
         }
-        Some((Self{parsed: inner.0}, inner.1))
+        let written_len = inner.1.offset() - data.offset();
+        Some((
+            Self {
+                parsed: inner.0,
+                written_len,
+            },
+            inner.1,
+        ))
     }
 }
 
 impl Suffix {
-    pub fn parsed(&self) -> &str {
-        &self.parsed.parsed()
+    pub fn parsed(&self) -> &'static str {
+        self.parsed.parsed()
+    }
+
+    pub fn written_len(&self) -> usize {
+        self.written_len
     }
 
     pub fn into_no_e_suffix(self) -> Option<SuffixNoE> {
         if self.parsed().starts_with('e') || self.parsed().starts_with('E') {
             return None;
         }
-        Some(SuffixNoE{parsed: self})
+        Some(SuffixNoE { parsed: self })
     }
 }
 
@@ -43,12 +55,12 @@ impl tokenizer_trait::Token for SuffixNoE {
         if inner.0.parsed().starts_with('e') || inner.0.parsed().starts_with('E') {
             return None;
         }
-        Some((Self{parsed: inner.0}, inner.1))
+        Some((Self { parsed: inner.0 }, inner.1))
     }
 }
 
 impl SuffixNoE {
-    pub fn parsed(&self) -> &str {
+    pub fn parsed(&self) -> &'static str {
         self.parsed.parsed()
     }
 
@@ -56,3 +68,176 @@ impl SuffixNoE {
         self.parsed
     }
 }
+
+/// Suffix that gives an integer literal its type, like the `u8` in `1u8`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntegerSuffix {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+}
+
+impl IntegerSuffix {
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        Some(match suffix {
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "u128" => Self::U128,
+            "usize" => Self::Usize,
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "i128" => Self::I128,
+            "isize" => Self::Isize,
+            _ => return None,
+        })
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(
+            self,
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::I128 | Self::Isize
+        )
+    }
+}
+
+/// Suffix that gives a float literal its type, like the `f32` in `1.5f32`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatSuffix {
+    F32,
+    F64,
+}
+
+impl FloatSuffix {
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "f32" => Some(Self::F32),
+            "f64" => Some(Self::F64),
+            _ => None,
+        }
+    }
+}
+
+/// Checks the suffix of a literal the way rustc does once it's past lexing: numbers may only have
+/// a numeric type as suffix and every other literal none at all. Gives the error and how many
+/// bytes of the literal come before the suffix.
+pub(crate) fn check_suffix(kind: &TokenKind) -> Option<(LexErrorKind, usize)> {
+    let (literal, suffix, source) = match kind {
+        TokenKind::IntegerLiteral(literal) => {
+            literal.suffix()?;
+            let error = match (literal.integer_suffix(), literal.float_suffix()) {
+                (Some(_), _) => return None,
+                (None, Some(_)) if literal.radix() == 10 => return None,
+                // `f32` and `f64` are hex digits, so `0x1f32` is a plain hex number
+                (None, Some(_)) => LexErrorKind::NonDecimalFloatLiteral(literal.radix()),
+                (None, None) => LexErrorKind::InvalidIntegerSuffix,
+            };
+            return Some((error, literal.source().len()));
+        }
+        TokenKind::FloatLiteral(literal) => {
+            literal.suffix()?;
+            if literal.float_suffix().is_some() {
+                return None;
+            }
+            return Some((LexErrorKind::InvalidFloatSuffix, literal.source().len()));
+        }
+        TokenKind::CharLiteral(literal) => ("char", literal.suffix(), literal.source()),
+        TokenKind::ByteLiteral(literal) => ("byte", literal.suffix(), literal.source()),
+        TokenKind::StringLiteral(literal) => ("string", literal.suffix(), literal.source()),
+        TokenKind::RawStringLiteral(literal) => ("string", literal.suffix(), literal.source()),
+        TokenKind::ByteStringLiteral(literal) => {
+            ("byte string", literal.suffix(), literal.source())
+        }
+        TokenKind::RawByteStringLiteral(literal) => {
+            ("byte string", literal.suffix(), literal.source())
+        }
+        TokenKind::CStringLiteral(literal) => ("C string", literal.suffix(), literal.source()),
+        TokenKind::RawCStringLiteral(literal) => ("C string", literal.suffix(), literal.source()),
+        _ => return None,
+    };
+    suffix?;
+    Some((LexErrorKind::InvalidLiteralSuffix(literal), source.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::*;
+    use crate::{TokenizeOptions, span::FileId, tokenize};
+
+    /// Every error in `src`, as its kind and byte range
+    fn errors(src: &str, proc_macro: bool) -> Vec<(LexErrorKind, Range<usize>)> {
+        let options = TokenizeOptions {
+            proc_macro,
+            ..TokenizeOptions::default()
+        };
+        match tokenize(SrcIterator::new(src), FileId::default(), &options) {
+            Ok(_) => Vec::new(),
+            Err(error) => error
+                .errors
+                .iter()
+                .map(|error| (error.kind, error.span.byte_range()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn invalid_suffixes() {
+        let src = r#"1u7 "x"suffix 1.0u8 0b1f32 0o7f64 'c'é b"b"_x"#;
+        let expected = [
+            (LexErrorKind::InvalidIntegerSuffix, 1..3),
+            (LexErrorKind::InvalidLiteralSuffix("string"), 7..13),
+            (LexErrorKind::InvalidFloatSuffix, 17..19),
+            (LexErrorKind::NonDecimalFloatLiteral(2), 23..26),
+            (LexErrorKind::NonDecimalFloatLiteral(8), 30..33),
+            (LexErrorKind::InvalidLiteralSuffix("char"), 37..39),
+            (LexErrorKind::InvalidLiteralSuffix("byte string"), 44..46),
+        ];
+        assert_eq!(errors(src, false), expected);
+    }
+
+    #[test]
+    fn valid_suffixes() {
+        assert_eq!(
+            errors("1u8 1i128 1usize 1.0f32 1f64 1e3f32 2.5E-3f64", false),
+            []
+        );
+    }
+
+    #[test]
+    fn hex_digits_are_not_a_suffix() {
+        let src = "0x1f32 0x1F64";
+        assert_eq!(errors(src, false), []);
+        let tokens = tokenize(
+            SrcIterator::new(src),
+            FileId::default(),
+            &Default::default(),
+        );
+        for token in tokens.unwrap() {
+            let TokenKind::IntegerLiteral(literal) = &token.kind else {
+                panic!("expected an integer, got {:?}", token.kind);
+            };
+            assert_eq!(literal.radix(), 16);
+            assert_eq!(literal.suffix(), None);
+        }
+    }
+
+    #[test]
+    fn proc_macros_take_any_suffix() {
+        let src = r#"1u7 "x"suffix 1.0u8 0b1f32 'c'é b"b"_x"#;
+        assert_eq!(errors(src, true), []);
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    sync::{LazyLock, RwLock},
+};
+
+/// An interned string. Equal strings get the same symbol, so comparing and hashing symbols is
+/// as cheap as for a `u32`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+macro_rules! keywords {
+    ($($name:ident: $string:literal,)*) => {
+        #[repr(u32)]
+        enum KeywordIndex {
+            $($name,)*
+        }
+
+        /// Keywords are interned up front, so their symbols are known at compile time
+        #[allow(non_upper_case_globals)]
+        pub mod kw {
+            use super::{KeywordIndex, Symbol};
+
+            $(pub const $name: Symbol = Symbol(KeywordIndex::$name as u32);)*
+        }
+
+        const PREINTERNED: &[&str] = &[$($string,)*];
+    };
+}
+
+// Everything `crate::keyword::Keyword` can be
+keywords! {
+    Underscore: "_",
+    As: "as",
+    Async: "async",
+    Await: "await",
+    Break: "break",
+    Const: "const",
+    Continue: "continue",
+    Crate: "crate",
+    Dyn: "dyn",
+    Else: "else",
+    Enum: "enum",
+    Extern: "extern",
+    False: "false",
+    Fn: "fn",
+    For: "for",
+    If: "if",
+    Impl: "impl",
+    In: "in",
+    Let: "let",
+    Loop: "loop",
+    Match: "match",
+    Mod: "mod",
+    Move: "move",
+    Mut: "mut",
+    Pub: "pub",
+    Ref: "ref",
+    Return: "return",
+    SelfLower: "self",
+    SelfUpper: "Self",
+    Static: "static",
+    Struct: "struct",
+    Super: "super",
+    Trait: "trait",
+    True: "true",
+    Type: "type",
+    Unsafe: "unsafe",
+    Use: "use",
+    Where: "where",
+    While: "while",
+
+    Abstract: "abstract",
+    Become: "become",
+    Box: "box",
+    Do: "do",
+    Final: "final",
+    Gen: "gen",
+    Macro: "macro",
+    Override: "override",
+    Priv: "priv",
+    Try: "try",
+    Typeof: "typeof",
+    Unsized: "unsized",
+    Virtual: "virtual",
+    Yield: "yield",
+
+    MacroRules: "macro_rules",
+    Raw: "raw",
+    Safe: "safe",
+    StaticLifetime: "'static",
+    Union: "union",
+}
+
+struct Interner {
+    symbols: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
+}
+
+impl Interner {
+    fn prefilled() -> Self {
+        let strings = PREINTERNED.to_vec();
+        let symbols = strings
+            .iter()
+            .enumerate()
+            .map(|(i, string)| (*string, Symbol(i as u32)))
+            .collect();
+        Self { symbols, strings }
+    }
+}
+
+// Interned strings are never freed, they live as long as the process. That's why only names,
+// keywords and suffixes get interned, and literal and comment text is owned by its token.
+static INTERNER: LazyLock<RwLock<Interner>> = LazyLock::new(|| RwLock::new(Interner::prefilled()));
+
+impl Symbol {
+    pub fn intern(string: &str) -> Self {
+        if let Some(symbol) = INTERNER.read().unwrap().symbols.get(string) {
+            return *symbol;
+        }
+        let mut interner = INTERNER.write().unwrap();
+        // someone else might have interned it between the two locks
+        if let Some(symbol) = interner.symbols.get(string) {
+            return *symbol;
+        }
+        let string: &'static str = Box::leak(string.into());
+        let symbol = Self(interner.strings.len() as u32);
+        interner.strings.push(string);
+        interner.symbols.insert(string, symbol);
+        symbol
+    }
+
+    pub fn as_str(self) -> &'static str {
+        INTERNER.read().unwrap().strings[self.0 as usize]
+    }
+
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+impl Debug for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
@@ -0,0 +1,495 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    Token, TokenKind,
+    punctuation::{Punctuation, Spacing},
+    span::Span,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Delimiter {
+    /// `( ... )`
+    Parenthesis,
+    /// `{ ... }`
+    Brace,
+    /// `[ ... ]`
+    Bracket,
+}
+
+impl Delimiter {
+    fn open(&self) -> char {
+        match self {
+            Self::Parenthesis => '(',
+            Self::Brace => '{',
+            Self::Bracket => '[',
+        }
+    }
+
+    fn close(&self) -> char {
+        match self {
+            Self::Parenthesis => ')',
+            Self::Brace => '}',
+            Self::Bracket => ']',
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TokenTree {
+    Leaf(Token),
+    Group(Group),
+}
+
+impl TokenTree {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Leaf(token) => token.span,
+            Self::Group(group) => group.span(),
+        }
+    }
+}
+
+/// Tokens between a pair of matching delimiters
+#[derive(Debug)]
+pub struct Group {
+    pub delimiter: Delimiter,
+    pub open_span: Span,
+    /// Empty span where the group was cut off if the closing delimiter is missing
+    pub close_span: Span,
+    pub stream: Box<[TokenTree]>,
+}
+
+impl Group {
+    /// Span from the opening to the closing delimiter
+    pub fn span(&self) -> Span {
+        self.open_span.to(self.close_span)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelimiterErrorKind {
+    /// Never closed before the end of the file or of the enclosing group
+    Unclosed(Delimiter),
+    /// Closing delimiter without an opening one
+    Unexpected(Delimiter),
+    /// Closing delimiter that doesn't match the innermost open one
+    Mismatched { open: Delimiter, close: Delimiter },
+}
+
+impl Display for DelimiterErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unclosed(delimiter) => write!(f, "unclosed delimiter `{}`", delimiter.open()),
+            Self::Unexpected(delimiter) => {
+                write!(f, "unexpected closing delimiter `{}`", delimiter.close())
+            }
+            Self::Mismatched { open, close } => write!(
+                f,
+                "mismatched closing delimiter `{}` for `{}`",
+                close.close(),
+                open.open()
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelimiterError {
+    pub kind: DelimiterErrorKind,
+    pub span: Span,
+    /// The opening delimiter a mismatched closing delimiter was checked against
+    pub open_span: Option<Span>,
+    /// Delimiter that most likely causes the error, guessed from indentation
+    pub culprit: Option<Span>,
+}
+
+impl Display for DelimiterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.span, self.kind)?;
+        if let Some(open_span) = self.open_span {
+            write!(f, "\n{}: note: unclosed delimiter", open_span)?;
+        }
+        if let Some(culprit) = self.culprit {
+            write!(
+                f,
+                "\n{}: note: this delimiter might not be properly closed",
+                culprit
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DelimiterError {}
+
+/// Returned when delimiters don't balance. `trees` still holds every token: unclosed groups are
+/// closed where the error was found, and unexpected closing delimiters are dropped.
+#[derive(Debug)]
+pub struct TokenTreeError {
+    pub trees: Box<[TokenTree]>,
+    pub errors: Box<[DelimiterError]>,
+}
+
+impl Display for TokenTreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TokenTreeError {}
+
+struct OpenGroup {
+    delimiter: Delimiter,
+    open_span: Span,
+    stream: Vec<TokenTree>,
+}
+
+impl OpenGroup {
+    fn close(self, close_span: Span) -> TokenTree {
+        TokenTree::Group(Group {
+            delimiter: self.delimiter,
+            open_span: self.open_span,
+            close_span,
+            stream: self.stream.into_boxed_slice(),
+        })
+    }
+}
+
+/// Groups a token stream into balanced [`TokenTree`]s
+pub fn build_token_trees(
+    tokens: impl IntoIterator<Item = Token>,
+) -> Result<Box<[TokenTree]>, TokenTreeError> {
+    let tokens: Vec<Token> = tokens.into_iter().collect();
+    let indentation = Indentation::new(&tokens);
+    let mut top = Vec::new();
+    let mut open: Vec<OpenGroup> = Vec::new();
+    let mut errors = Vec::new();
+    // pairs that closed at a different indentation than they opened at, the usual sign that they
+    // took someone else's closing delimiter
+    let mut misindented = Vec::new();
+    let mut end = None;
+
+    for token in tokens {
+        end = Some(token.span);
+        let Some((delimiter, is_open)) = delimiter(&token.kind) else {
+            match open.last_mut() {
+                Some(group) => group.stream.push(TokenTree::Leaf(token)),
+                None => top.push(TokenTree::Leaf(token)),
+            }
+            continue;
+        };
+        if is_open {
+            open.push(OpenGroup {
+                delimiter,
+                open_span: token.span,
+                stream: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(matching) = open.iter().rposition(|group| group.delimiter == delimiter) else {
+            errors.push(DelimiterError {
+                kind: DelimiterErrorKind::Unexpected(delimiter),
+                span: token.span,
+                open_span: None,
+                culprit: misindented.last().copied(),
+            });
+            continue;
+        };
+        if matching != open.len() - 1 {
+            let innermost = open.last().unwrap();
+            errors.push(DelimiterError {
+                kind: DelimiterErrorKind::Mismatched {
+                    open: innermost.delimiter,
+                    close: delimiter,
+                },
+                span: token.span,
+                open_span: Some(innermost.open_span),
+                // already pointed at as the unclosed delimiter
+                culprit: indentation
+                    .unclosed_culprit(&open[matching + 1..], token.span)
+                    .filter(|culprit| *culprit != innermost.open_span),
+            });
+        }
+        // everything opened after the matching delimiter ends here without being closed
+        while open.len() > matching + 1 {
+            let group = open.pop().unwrap();
+            let tree = group.close(token.span.shrink_to_start());
+            open.last_mut().unwrap().stream.push(tree);
+        }
+
+        let group = open.pop().unwrap();
+        if indentation.of(group.open_span) != indentation.of(token.span) {
+            misindented.push(group.open_span);
+        }
+        let tree = group.close(token.span);
+        match open.last_mut() {
+            Some(group) => group.stream.push(tree),
+            None => top.push(tree),
+        }
+    }
+
+    // groups still open at the end of the stream are closed right after its last token
+    let end = end.map(|span| Span::new(span.file, span.end, span.end));
+    if let Some(end) = end {
+        for (i, group) in open.iter().enumerate() {
+            errors.push(DelimiterError {
+                kind: DelimiterErrorKind::Unclosed(group.delimiter),
+                span: group.open_span,
+                open_span: None,
+                culprit: indentation
+                    .unclosed_culprit(&open[i..], end)
+                    .filter(|culprit| *culprit != group.open_span),
+            });
+        }
+    }
+    while let Some(group) = open.pop() {
+        let tree = group.close(end.unwrap());
+        match open.last_mut() {
+            Some(group) => group.stream.push(tree),
+            None => top.push(tree),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(top.into_boxed_slice())
+    } else {
+        Err(TokenTreeError {
+            trees: top.into_boxed_slice(),
+            errors: errors.into_boxed_slice(),
+        })
+    }
+}
+
+/// Turns token trees back into the flat stream the parser takes. Delimiters become punctuation
+/// tokens again, including the closing delimiters of groups that were missing one.
+pub fn flatten_token_trees(trees: impl IntoIterator<Item = TokenTree>) -> Box<[Token]> {
+    let mut tokens = Vec::new();
+    flatten_into(&mut tokens, trees);
+    tokens.into_boxed_slice()
+}
+
+fn flatten_into(tokens: &mut Vec<Token>, trees: impl IntoIterator<Item = TokenTree>) {
+    for tree in trees {
+        let group = match tree {
+            TokenTree::Leaf(token) => {
+                tokens.push(token);
+                continue;
+            }
+            TokenTree::Group(group) => group,
+        };
+        let (open, close) = match group.delimiter {
+            Delimiter::Parenthesis => (Punctuation::LeftParen, Punctuation::RightParen),
+            Delimiter::Brace => (Punctuation::LeftCurly, Punctuation::RightCurly),
+            Delimiter::Bracket => (Punctuation::LeftSquare, Punctuation::RightSquare),
+        };
+        tokens.push(delimiter_token(open, group.open_span));
+        flatten_into(tokens, group.stream);
+        tokens.push(delimiter_token(close, group.close_span));
+    }
+}
+
+fn delimiter_token(punctuation: Punctuation, span: Span) -> Token {
+    Token {
+        kind: TokenKind::Punctuation(punctuation),
+        span,
+        spacing: Spacing::Alone,
+        lossless: None,
+    }
+}
+
+/// Which delimiter a token is and whether it opens a group
+fn delimiter(kind: &TokenKind) -> Option<(Delimiter, bool)> {
+    let TokenKind::Punctuation(punctuation) = kind else {
+        return None;
+    };
+    match punctuation {
+        Punctuation::LeftParen => Some((Delimiter::Parenthesis, true)),
+        Punctuation::RightParen => Some((Delimiter::Parenthesis, false)),
+        Punctuation::LeftCurly => Some((Delimiter::Brace, true)),
+        Punctuation::RightCurly => Some((Delimiter::Brace, false)),
+        Punctuation::LeftSquare => Some((Delimiter::Bracket, true)),
+        Punctuation::RightSquare => Some((Delimiter::Bracket, false)),
+        _ => None,
+    }
+}
+
+/// Column of the first token on every line
+struct Indentation {
+    lines: HashMap<u32, u32>,
+    /// Lines that have a token on them, in order
+    starts: Vec<(u32, Span)>,
+}
+
+impl Indentation {
+    fn new(tokens: &[Token]) -> Self {
+        let mut lines = HashMap::new();
+        let mut starts = Vec::new();
+        for token in tokens {
+            if let std::collections::hash_map::Entry::Vacant(entry) =
+                lines.entry(token.span.start.line)
+            {
+                entry.insert(token.span.start.column);
+                starts.push((token.span.start.line, token.span));
+            }
+        }
+        Self { lines, starts }
+    }
+
+    fn of(&self, span: Span) -> u32 {
+        self.lines[&span.start.line]
+    }
+
+    /// Of the delimiters in `unclosed` (outermost first), the one that most likely misses its
+    /// closing delimiter. A block normally ends at the indentation it started at, so that is the
+    /// innermost delimiter after which a line before `end` goes back to its indentation.
+    fn unclosed_culprit(&self, unclosed: &[OpenGroup], end: Span) -> Option<Span> {
+        unclosed.iter().rev().find_map(|group| {
+            let indentation = self.of(group.open_span);
+            let first_line = self
+                .starts
+                .partition_point(|(line, _)| *line <= group.open_span.start.line);
+            self.starts[first_line..]
+                .iter()
+                .take_while(|(_, span)| span.start < end.start)
+                .any(|(line, _)| self.lines[line] <= indentation)
+                .then_some(group.open_span)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokenizer_trait::SrcIterator;
+
+    use super::*;
+    use crate::{TokenizeOptions, span::FileId, tokenize};
+
+    fn trees(src: &str) -> Result<Box<[TokenTree]>, TokenTreeError> {
+        let tokens = tokenize(
+            SrcIterator::new(src),
+            FileId::default(),
+            &TokenizeOptions::default(),
+        )
+        .unwrap();
+        build_token_trees(tokens)
+    }
+
+    fn errors(src: &str) -> Vec<String> {
+        let errors = trees(src).unwrap_err().errors;
+        errors.iter().map(|error| error.to_string()).collect()
+    }
+
+    #[test]
+    fn balanced() {
+        let trees = trees("a(b[c]{d})").unwrap();
+        assert_eq!(trees.len(), 2);
+        let TokenTree::Group(group) = &trees[1] else {
+            panic!("expected a group, got {:?}", trees[1]);
+        };
+        assert_eq!(group.delimiter, Delimiter::Parenthesis);
+        assert_eq!(group.span().byte_range(), 1..10);
+        assert_eq!(group.stream.len(), 3);
+    }
+
+    #[test]
+    fn unexpected() {
+        assert_eq!(errors("a)"), ["1:2: unexpected closing delimiter `)`"]);
+        // the closing delimiter is dropped
+        let error = trees("(a]b)").unwrap_err();
+        assert_eq!(error.trees.len(), 1);
+        assert_eq!(error.trees[0].span().byte_range(), 0..5);
+    }
+
+    #[test]
+    fn mismatched() {
+        assert_eq!(
+            errors("{ (a }"),
+            ["1:6: mismatched closing delimiter `}` for `(`\n1:3: note: unclosed delimiter"]
+        );
+        // the unclosed group ends where the mismatched delimiter is
+        let error = trees("{ (a }").unwrap_err();
+        let TokenTree::Group(outer) = &error.trees[0] else {
+            panic!("expected a group, got {:?}", error.trees[0]);
+        };
+        assert_eq!(outer.close_span.byte_range(), 5..6);
+        let TokenTree::Group(inner) = &outer.stream[0] else {
+            panic!("expected a group, got {:?}", outer.stream[0]);
+        };
+        assert_eq!(inner.close_span.byte_range(), 5..5);
+    }
+
+    #[test]
+    fn unclosed() {
+        assert_eq!(
+            errors("a { b ( c"),
+            ["1:3: unclosed delimiter `{`", "1:7: unclosed delimiter `(`"]
+        );
+        let error = trees("a {").unwrap_err();
+        let TokenTree::Group(group) = &error.trees[1] else {
+            panic!("expected a group, got {:?}", error.trees[1]);
+        };
+        assert_eq!(group.close_span.byte_range(), 3..3);
+    }
+
+    #[test]
+    fn unclosed_is_not_its_own_culprit() {
+        assert_eq!(
+            errors("fn a() {\n    x\n\nfn b() {\n}\n"),
+            ["1:8: unclosed delimiter `{`"]
+        );
+    }
+
+    #[test]
+    fn unclosed_culprit() {
+        // `b`'s block goes back to its indentation without being closed, so `a` is probably only
+        // unclosed because of it
+        let src = "fn a() {\n    fn b() {\n        x\n    y\n";
+        assert_eq!(
+            errors(src),
+            [
+                "1:8: unclosed delimiter `{`\n2:12: note: this delimiter might not be properly \
+                 closed",
+                "2:12: unclosed delimiter `{`",
+            ]
+        );
+    }
+
+    #[test]
+    fn mismatched_culprit() {
+        let src = "{\n    [\n        x\n    y (\n}\n";
+        assert_eq!(
+            errors(src),
+            [
+                "5:1: mismatched closing delimiter `}` for `(`\n4:7: note: unclosed delimiter\n\
+                 2:5: note: this delimiter might not be properly closed"
+            ]
+        );
+        // the innermost delimiter is already pointed at
+        let src = "{\n    (\n    x\n}\n";
+        assert_eq!(
+            errors(src),
+            ["4:1: mismatched closing delimiter `}` for `(`\n2:5: note: unclosed delimiter"]
+        );
+    }
+
+    #[test]
+    fn unexpected_culprit() {
+        // `a` closed at a different indentation than it opened at, so it likely took the `}` that
+        // is left over
+        let src = "fn a() {\n    if x {\n        y\n    }}\n}\n";
+        assert_eq!(
+            errors(src),
+            [
+                "5:1: unexpected closing delimiter `}`\n1:8: note: this delimiter might not be \
+                 properly closed"
+            ]
+        );
+    }
+}
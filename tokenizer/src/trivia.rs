@@ -0,0 +1,63 @@
+use crate::{Token, span::Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// A run of whitespace that doesn't contain a line break
+    Whitespace,
+    /// `\n` or `\r\n`
+    Newline,
+    LineComment,
+    BlockComment,
+    ByteOrderMark,
+}
+
+#[derive(Debug, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+    pub text: Box<str>,
+}
+
+/// Exact source text of a token and the trivia around it, only filled in lossless mode.
+///
+/// Trivia after a token up to (not including) the next line break is trailing trivia of that
+/// token, everything else is leading trivia of the token that follows.
+#[derive(Debug, Clone, Default)]
+pub struct LosslessText {
+    pub leading: Box<[Trivia]>,
+    pub text: Box<str>,
+    pub trailing: Box<[Trivia]>,
+}
+
+impl LosslessText {
+    pub fn write_to(&self, out: &mut String) {
+        self.leading
+            .iter()
+            .for_each(|trivia| out.push_str(&trivia.text));
+        out.push_str(&self.text);
+        self.trailing
+            .iter()
+            .for_each(|trivia| out.push_str(&trivia.text));
+    }
+}
+
+/// Prints tokens back out, trivia included. For a stream lexed in lossless mode this gives back
+/// the exact input. `None` if some token has no [`LosslessText`].
+pub fn print_lossless(tokens: &[Token]) -> Option<String> {
+    let mut out = String::new();
+    for token in tokens {
+        token.lossless.as_ref()?.write_to(&mut out);
+    }
+    Some(out)
+}
+
+/// Splits the trivia between two tokens into the trailing trivia of the first and the leading
+/// trivia of the second
+pub(crate) fn split_trivia(mut trivia: Vec<Trivia>) -> (Vec<Trivia>, Vec<Trivia>) {
+    let newline = trivia
+        .iter()
+        .position(|trivia| trivia.kind == TriviaKind::Newline)
+        .unwrap_or(trivia.len());
+    let leading = trivia.split_off(newline);
+    (trivia, leading)
+}
@@ -0,0 +1,325 @@
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    fmt::Display,
+};
+
+use unicode_security::{
+    GeneralSecurityProfile, is_potential_mixed_script_confusable_char,
+    mixed_script::AugmentedScriptSet, skeleton,
+};
+
+use crate::{
+    Token, TokenKind,
+    span::{FileId, PosTracker, Span},
+};
+
+/// Codepoints that change the direction text is displayed in. They can make code read differently
+/// from how it compiles.
+const TEXT_DIRECTION_CODEPOINTS: &[char] = &[
+    '\u{202A}', '\u{202B}', '\u{202C}', '\u{202D}', '\u{202E}', '\u{2066}', '\u{2067}', '\u{2068}',
+    '\u{2069}',
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnicodeLintKind {
+    /// rustc's `text_direction_codepoint_in_literal`
+    TextDirectionCodepointInLiteral(char),
+    /// rustc's `text_direction_codepoint_in_comment`, also for the shebang and frontmatter
+    TextDirectionCodepointInComment(char),
+    /// rustc's `confusable_idents`: looks the same as an earlier, different identifier
+    ConfusableIdentifier,
+    /// rustc's `mixed_script_confusables`: every letter of this script in the file looks like a
+    /// letter of another script, like a lone Cyrillic `а` among Latin identifiers
+    MixedScriptConfusables(AugmentedScriptSet),
+}
+
+impl Display for UnicodeLintKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TextDirectionCodepointInLiteral(chr) => write!(
+                f,
+                "unicode codepoint changing visible direction of text present in literal: {:?}",
+                chr
+            ),
+            Self::TextDirectionCodepointInComment(chr) => write!(
+                f,
+                "unicode codepoint changing visible direction of text present in comment: {:?}",
+                chr
+            ),
+            Self::ConfusableIdentifier => write!(f, "identifier pair considered confusable"),
+            Self::MixedScriptConfusables(script_set) => write!(
+                f,
+                "the usage of Script Group `{}` in this file consists solely of mixed script \
+                 confusables",
+                script_set
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnicodeLint {
+    pub kind: UnicodeLintKind,
+    pub span: Span,
+    /// The earlier identifier a confusable identifier looks like
+    pub confusable_with: Option<Span>,
+}
+
+impl Display for UnicodeLint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: warning: {}", self.span, self.kind)?;
+        if let Some(other) = self.confusable_with {
+            write!(f, "\n{}: note: this identifier looks the same", other)?;
+        }
+        Ok(())
+    }
+}
+
+/// Looks for unicode that can make code read differently from what it does. `src` is the text
+/// `tokens` were lexed from, so comments that aren't in the stream are found between the tokens.
+pub fn check_unicode(tokens: &[Token], src: &str, file: FileId) -> Box<[UnicodeLint]> {
+    let mut lints = Vec::new();
+    let mut positions = PosTracker::new(src);
+    let mut text_direction = |lints: &mut Vec<UnicodeLint>, start: usize, end: usize, literal| {
+        for (i, chr) in src[start..end].match_indices(TEXT_DIRECTION_CODEPOINTS) {
+            let chr = chr.chars().next().unwrap();
+            let kind = match literal {
+                true => UnicodeLintKind::TextDirectionCodepointInLiteral(chr),
+                false => UnicodeLintKind::TextDirectionCodepointInComment(chr),
+            };
+            let offset = start + i;
+            lints.push(UnicodeLint {
+                kind,
+                span: positions.span(file, offset..offset + chr.len_utf8()),
+                confusable_with: None,
+            });
+        }
+    };
+
+    // identifiers are only compared the first time they show up, lifetimes apart from the rest
+    let mut seen: HashSet<(&str, bool)> = HashSet::new();
+    // first identifier with every skeleton, preferring non-ascii ones like rustc does
+    let mut skeletons: HashMap<String, (Span, bool)> = HashMap::new();
+    // in the order they show up, there are only ever a few. Latin is what the ascii letters are,
+    // so it doesn't count as confusable.
+    let mut script_sets = vec![(AugmentedScriptSet::for_char('A'), ScriptSetUsage::Verified)];
+    let mut gap_start = 0;
+    for token in tokens {
+        let range = token.span.byte_range();
+        // whatever isn't part of a token is whitespace or comments
+        text_direction(&mut lints, gap_start, range.start, false);
+        gap_start = range.end;
+
+        let (name, is_lifetime) = match &token.kind {
+            TokenKind::CharLiteral(_)
+            | TokenKind::StringLiteral(_)
+            | TokenKind::RawStringLiteral(_)
+            | TokenKind::CStringLiteral(_)
+            | TokenKind::RawCStringLiteral(_) => {
+                text_direction(&mut lints, range.start, range.end, true);
+                continue;
+            }
+            TokenKind::DocComment(_)
+            | TokenKind::Comment(_)
+            | TokenKind::Shebang(_)
+            | TokenKind::Frontmatter(_) => {
+                text_direction(&mut lints, range.start, range.end, false);
+                continue;
+            }
+            TokenKind::IdentifierOrKeyword(ident) => (ident.parsed(), false),
+            TokenKind::RawIdentifier(ident) => (ident.parsed(), false),
+            TokenKind::LifetimeToken(lifetime) => (lifetime.name(), true),
+            _ => continue,
+        };
+        if !seen.insert((name, is_lifetime)) {
+            continue;
+        }
+        let is_ascii = name.is_ascii();
+        for chr in name.chars() {
+            // rustc leaves characters that aren't allowed at all to `uncommon_codepoints`
+            if chr.is_ascii() || !chr.identifier_allowed() {
+                continue;
+            }
+            let confusable = is_potential_mixed_script_confusable_char(chr);
+            let script_set = AugmentedScriptSet::for_char(chr);
+            match script_sets
+                .iter_mut()
+                .find(|(other, _)| *other == script_set)
+            {
+                Some((_, usage)) if !confusable => *usage = ScriptSetUsage::Verified,
+                Some(_) => {}
+                None if confusable => {
+                    script_sets.push((script_set, ScriptSetUsage::Suspicious(token.span)))
+                }
+                None => script_sets.push((script_set, ScriptSetUsage::Verified)),
+            }
+        }
+        // the `'` keeps lifetimes apart from identifiers
+        let key = is_lifetime
+            .then_some('\'')
+            .into_iter()
+            .chain(skeleton(name));
+        match skeletons.entry(key.collect()) {
+            Entry::Occupied(mut entry) => {
+                let (other, other_is_ascii) = entry.get_mut();
+                if !*other_is_ascii || !is_ascii {
+                    lints.push(UnicodeLint {
+                        kind: UnicodeLintKind::ConfusableIdentifier,
+                        span: token.span,
+                        confusable_with: Some(*other),
+                    });
+                }
+                if *other_is_ascii && !is_ascii {
+                    *other = token.span;
+                    *other_is_ascii = false;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((token.span, is_ascii));
+            }
+        }
+    }
+    text_direction(&mut lints, gap_start, src.len(), false);
+
+    lints.extend(mixed_script_confusables(&script_sets));
+    // stable, so lints on the same identifier stay in the order they were found
+    lints.sort_by_key(|lint| lint.span.start.offset);
+    lints.into_boxed_slice()
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ScriptSetUsage {
+    /// Every character of the script set so far is confusable, the span is the first identifier
+    /// with one
+    Suspicious(Span),
+    Verified,
+}
+
+/// Script sets only used for characters that look like ones from another script. A script set
+/// that shares a script with one that is used for real, like Han with Japanese, is left alone.
+fn mixed_script_confusables(
+    script_sets: &[(AugmentedScriptSet, ScriptSetUsage)],
+) -> impl Iterator<Item = UnicodeLint> {
+    let verified: Vec<AugmentedScriptSet> = script_sets
+        .iter()
+        .filter(|(script_set, usage)| {
+            matches!(usage, ScriptSetUsage::Verified) && !script_set.is_all()
+        })
+        .map(|(script_set, _)| *script_set)
+        .collect();
+    script_sets.iter().filter_map(move |(script_set, usage)| {
+        let ScriptSetUsage::Suspicious(span) = usage else {
+            return None;
+        };
+        if script_set.is_all() {
+            return None;
+        }
+        let overlaps = verified.iter().any(|other| {
+            let mut intersection = *other;
+            intersection.intersect_with(*script_set);
+            !intersection.is_empty() && !intersection.is_all()
+        });
+        (!overlaps).then_some(UnicodeLint {
+            kind: UnicodeLintKind::MixedScriptConfusables(*script_set),
+            span: *span,
+            confusable_with: None,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use tokenizer_trait::SrcIterator;
+
+    use super::*;
+    use crate::{TokenizeOptions, tokenize};
+
+    /// Every lint in `src`, as its kind, byte range and the byte range of what it is confusable
+    /// with
+    fn lints(src: &str) -> Vec<(UnicodeLintKind, Range<usize>, Option<Range<usize>>)> {
+        let tokens = tokenize(
+            SrcIterator::new(src),
+            FileId::default(),
+            &TokenizeOptions::default(),
+        )
+        .unwrap();
+        check_unicode(&tokens, src, FileId::default())
+            .iter()
+            .map(|lint| {
+                let other = lint.confusable_with.map(|span| span.byte_range());
+                (lint.kind, lint.span.byte_range(), other)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn text_direction() {
+        let src = "let s = \"a\u{202E}b\"; // c\u{2066}\n/// d\u{2067}\nfn f() {}";
+        assert_eq!(
+            lints(src),
+            [
+                (
+                    UnicodeLintKind::TextDirectionCodepointInLiteral('\u{202E}'),
+                    10..13,
+                    None
+                ),
+                (
+                    UnicodeLintKind::TextDirectionCodepointInComment('\u{2066}'),
+                    21..24,
+                    None
+                ),
+                (
+                    UnicodeLintKind::TextDirectionCodepointInComment('\u{2067}'),
+                    30..33,
+                    None
+                ),
+            ]
+        );
+        assert_eq!(lints("/* \u{202D} */ x \"\u{202C}\"").len(), 2);
+    }
+
+    #[test]
+    fn cyrillic_a() {
+        let cyrillic = AugmentedScriptSet::for_char('а');
+        assert_eq!(
+            lints("let a = 1; let а = 2;"),
+            [
+                (UnicodeLintKind::ConfusableIdentifier, 15..17, Some(4..5)),
+                (
+                    UnicodeLintKind::MixedScriptConfusables(cyrillic),
+                    15..17,
+                    None
+                ),
+            ]
+        );
+        // the non-ascii one is what later identifiers are said to look like
+        assert_eq!(
+            lints("а a a"),
+            [
+                (
+                    UnicodeLintKind::MixedScriptConfusables(cyrillic),
+                    0..2,
+                    None
+                ),
+                (UnicodeLintKind::ConfusableIdentifier, 3..4, Some(0..2)),
+            ]
+        );
+        // a lifetime doesn't look like an identifier
+        assert_eq!(lints("'a a").len(), 0);
+    }
+
+    #[test]
+    fn nfc_names_are_the_same() {
+        // `e` and a combining acute accent, then a precomposed `é`
+        assert_eq!(lints("let e\u{301} = 1; é + é"), []);
+    }
+
+    #[test]
+    fn scripts_used_for_real() {
+        assert_eq!(lints("let 変数x = 1; let y = 変数x;"), []);
+        // a Cyrillic letter that looks like nothing else vouches for the rest of Cyrillic
+        assert_eq!(lints("let а = 1; let ж = 2;"), []);
+    }
+}
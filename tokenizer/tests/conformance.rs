@@ -0,0 +1,271 @@
+//! Differential tests against proc_macro2's lexer. Every file in `tests/corpus` has to lex to the
+//! same tokens with both lexers, and every file in `tests/corpus/invalid` has to be rejected by
+//! both.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use tokenizer::{
+    Edition, TokenKind, TokenizeOptions, comment::DocStyle, lex_error::TokenizeFileError,
+    tokenize_file,
+};
+
+/// proc_macro2 lexes like rustc does in the 2021 edition
+const EDITION: Edition = Edition::Edition2021;
+
+/// Files in `tests/corpus/invalid` that proc_macro2 accepts because it doesn't implement the rule
+/// they break. Only our side is checked for those.
+const REFERENCE_ACCEPTS: &[(&str, &str)] = &[
+    (
+        "reserved_prefix_char.rs",
+        "proc_macro2 lexes `foo'x'` as an identifier and a char",
+    ),
+    (
+        "reserved_prefix_string.rs",
+        "proc_macro2 lexes `foo\"bar\"` as an identifier and a string",
+    ),
+];
+
+/// Tokens by `(line, column)`, rendered the same way for both lexers. Punctuation is compared one
+/// char at a time and lifetimes as a `'` followed by an identifier, the way proc_macro2 has them.
+type Flat = BTreeMap<(u32, u32), Vec<String>>;
+
+#[test]
+fn corpus_lexes_like_proc_macro2() {
+    let mut divergences = Vec::new();
+    for path in corpus_files("tests/corpus") {
+        let src = fs::read_to_string(&path).unwrap();
+        let ours = match ours(&path) {
+            Ok(ours) => ours,
+            Err(error) => {
+                divergences.push(format!("{}: we fail to lex: {}", path.display(), error));
+                continue;
+            }
+        };
+        let reference = match without_shebang(&src).parse::<TokenStream>() {
+            Ok(stream) => reference(stream),
+            Err(error) => {
+                divergences.push(format!(
+                    "{}: proc_macro2 fails to lex: {}",
+                    path.display(),
+                    error
+                ));
+                continue;
+            }
+        };
+        divergences.extend(compare(&path, &ours, &reference));
+    }
+    assert!(divergences.is_empty(), "\n{}\n", divergences.join("\n"));
+}
+
+#[test]
+fn invalid_corpus_is_rejected_by_both() {
+    let mut divergences = Vec::new();
+    for path in corpus_files("tests/corpus/invalid") {
+        let src = fs::read_to_string(&path).unwrap();
+        if ours(&path).is_ok() {
+            divergences.push(format!("{}: we accept it", path.display()));
+        }
+        let name = path.file_name().unwrap().to_str().unwrap();
+        if REFERENCE_ACCEPTS.iter().any(|(file, _)| *file == name) {
+            continue;
+        }
+        if without_shebang(&src).parse::<TokenStream>().is_ok() {
+            divergences.push(format!("{}: proc_macro2 accepts it", path.display()));
+        }
+    }
+    assert!(divergences.is_empty(), "\n{}\n", divergences.join("\n"));
+}
+
+fn corpus_files(dir: &str) -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(dir);
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Every token position where the two lexers disagree
+fn compare(path: &Path, ours: &Flat, reference: &Flat) -> Vec<String> {
+    let mut positions: Vec<&(u32, u32)> = ours.keys().chain(reference.keys()).collect();
+    positions.sort();
+    positions.dedup();
+    positions
+        .into_iter()
+        .filter(|position| ours.get(position) != reference.get(position))
+        .map(|position @ (line, column)| {
+            format!(
+                "{}:{}:{}: ours {:?}, proc_macro2 {:?}",
+                path.display(),
+                line,
+                column,
+                ours.get(position).map_or(&[][..], Vec::as_slice),
+                reference.get(position).map_or(&[][..], Vec::as_slice),
+            )
+        })
+        .collect()
+}
+
+fn ours(path: &Path) -> Result<Flat, TokenizeFileError> {
+    let src = fs::read_to_string(path)?;
+    // proc_macro2 keeps any suffix, like a proc macro would see it
+    let options = TokenizeOptions {
+        edition: EDITION,
+        proc_macro: true,
+        ..Default::default()
+    };
+    let tokens = tokenize_file(path, &options)?;
+
+    let mut flat = Flat::new();
+    let mut push =
+        |line: u32, column: u32, text: String| flat.entry((line, column)).or_default().push(text);
+    for token in tokens.iter() {
+        let (line, column) = (token.span.start.line, token.span.start.column);
+        let text = &src[token.span.byte_range()];
+        match &token.kind {
+            // stripped before proc_macro2 sees the source
+            TokenKind::Shebang(_) => {}
+            TokenKind::DocComment(doc) => push(line, column, doc_text(doc.style(), doc.text())),
+            TokenKind::LifetimeToken(_) => {
+                push(line, column, punct('\'', true));
+                push(line, column + 1, text[1..].to_string());
+            }
+            TokenKind::Punctuation(punctuation) if punctuation.is_delimiter() => {
+                push(line, column, text.to_string())
+            }
+            TokenKind::Punctuation(_) => {
+                for (i, chr) in text.chars().enumerate() {
+                    let joint = i + 1 < text.len()
+                        || token.spacing == tokenizer::punctuation::Spacing::Joint;
+                    push(line, column + i as u32, punct(chr, joint));
+                }
+            }
+            _ => push(line, column, text.to_string()),
+        }
+    }
+    Ok(flat)
+}
+
+fn reference(stream: TokenStream) -> Flat {
+    let mut flat = Flat::new();
+    flatten_reference(&mut flat, stream);
+    flat
+}
+
+fn flatten_reference(flat: &mut Flat, stream: TokenStream) {
+    let trees: Vec<TokenTree> = stream.into_iter().collect();
+    let mut i = 0;
+    while i < trees.len() {
+        let start = trees[i].span().start();
+        let mut push = |text: String| {
+            flat.entry((start.line as u32, start.column as u32 + 1))
+                .or_default()
+                .push(text)
+        };
+        match &trees[i] {
+            TokenTree::Punct(_) if let Some((doc, len)) = reference_doc(&trees[i..]) => {
+                push(doc);
+                i += len;
+                continue;
+            }
+            TokenTree::Punct(p) => push(punct(p.as_char(), p.spacing() == Spacing::Joint)),
+            TokenTree::Ident(ident) => push(ident.to_string()),
+            TokenTree::Literal(literal) => push(literal.to_string()),
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                push(open.to_string());
+                flatten_reference(flat, group.stream());
+                let end = group.span_close().start();
+                flat.entry((end.line as u32, end.column as u32 + 1))
+                    .or_default()
+                    .push(close.to_string());
+            }
+        }
+        i += 1;
+    }
+}
+
+/// A doc comment turns into `#[doc = "..."]` with every token on the span of the comment, which
+/// tells it apart from an attribute that was written out
+fn reference_doc(trees: &[TokenTree]) -> Option<(String, usize)> {
+    let [TokenTree::Punct(hash), rest @ ..] = trees else {
+        return None;
+    };
+    let (style, group) = match rest {
+        [TokenTree::Punct(bang), TokenTree::Group(group), ..] if bang.as_char() == '!' => {
+            (DocStyle::Inner, group)
+        }
+        [TokenTree::Group(group), ..] => (DocStyle::Outer, group),
+        _ => return None,
+    };
+    if hash.as_char() != '#' || group.span_open().start() != hash.span().start() {
+        return None;
+    }
+    let literal = group.stream().into_iter().nth(2)?;
+    let len = match style {
+        DocStyle::Outer => 2,
+        DocStyle::Inner => 3,
+    };
+    Some((format!("{} {}", doc_prefix(style), literal), len))
+}
+
+fn punct(chr: char, joint: bool) -> String {
+    match joint {
+        true => format!("{} (joint)", chr),
+        false => chr.to_string(),
+    }
+}
+
+fn doc_prefix(style: DocStyle) -> &'static str {
+    match style {
+        DocStyle::Outer => "doc",
+        DocStyle::Inner => "inner doc",
+    }
+}
+
+fn doc_text(style: DocStyle, text: &str) -> String {
+    format!(
+        "{} {}",
+        doc_prefix(style),
+        proc_macro2::Literal::string(text)
+    )
+}
+
+/// proc_macro2 doesn't know about shebangs, rustc strips them before lexing. Per the reference a
+/// `#!` at the start of the file is a shebang unless the next token after it, skipping whitespace
+/// and comments, is a `[`. It is blanked out so the positions after it stay the same.
+fn without_shebang(src: &str) -> String {
+    let start = if src.starts_with('\u{feff}') { 3 } else { 0 };
+    let Some(rest) = src[start..].strip_prefix("#!") else {
+        return src.to_string();
+    };
+    let mut after = rest;
+    loop {
+        after = after.trim_start();
+        if let Some(comment) = after.strip_prefix("//") {
+            after = comment.find('\n').map_or("", |end| &comment[end..]);
+        } else if let Some(comment) = after.strip_prefix("/*") {
+            after = comment.find("*/").map_or("", |end| &comment[end + 2..]);
+        } else {
+            break;
+        }
+    }
+    if after.starts_with('[') {
+        return src.to_string();
+    }
+    let end = src[start..].find('\n').map_or(src.len(), |end| start + end);
+    let blank: String = src[start..end].chars().map(|_| ' ').collect();
+    format!("{}{}{}", &src[..start], blank, &src[end..])
+}
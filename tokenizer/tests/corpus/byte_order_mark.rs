@@ -0,0 +1 @@
+﻿fn with_byte_order_mark() {}
@@ -0,0 +1,22 @@
+//! inner line doc
+/*! inner block doc */
+
+/// outer line doc
+/** outer block doc */
+fn documented() {}
+
+// plain comment
+//// four slashes are a plain comment
+/* plain /* nested */ still a comment */
+/**/
+/***/
+/*** three stars are plain ***/
+///
+fn after_empty_doc() {}
+
+/** doc with /* a nested */ comment */
+fn nested_doc() {}
+
+fn code() {
+    let _ = 1 /* in the middle */ + 2; // trailing
+}
@@ -0,0 +1,8 @@
+/// doc
+fn main() {
+    let s = "a
+b";
+    // c
+    let r = r"x
+y";
+}
@@ -0,0 +1,2 @@
+/// bare  cr
+fn main() {}
@@ -0,0 +1 @@
+fn f() { let _ = c"nul \0"; }
@@ -0,0 +1 @@
+fn f() { let _ = foo'x'; }
@@ -0,0 +1 @@
+fn f() { let _ = r#self; }
@@ -0,0 +1,18 @@
+struct Borrowed<'a, 'b: 'a> {
+    x: &'a str,
+    y: &'b mut &'static str,
+    z: &'_ u8,
+}
+
+fn labels() {
+    'outer: loop {
+        'inner: while true {
+            break 'outer;
+        }
+        continue 'outer;
+    }
+    let _ = 'a';
+    let _ = '\'';
+}
+
+impl<'a> Borrowed<'a, 'a> {}
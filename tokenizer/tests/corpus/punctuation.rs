@@ -0,0 +1,17 @@
+fn punctuation(a: i32, b: &&i32) -> Vec<Vec<u8>> {
+    let c = a >>= 1;
+    let d = a <<= 2;
+    let _ = || a && b || c;
+    let _ = &&a;
+    let _ = a..=b;
+    let _ = ..;
+    let _ = Vec::<Vec<u8>>::new();
+    let _: Option<Option<u8>>= None;
+    let _ = a != b && a == b;
+    let _ = a -> b => c <- d;
+    let _ = #[attr] x?;
+    let _ = $x @ y ~ z;
+    let _ = a+-b*!c%^d;
+    let _ = a += b -= c *= d /= e %= f ^= g &= h |= i;
+    Vec::new()
+}
@@ -0,0 +1,13 @@
+// identifiers followed by literals with whitespace in between are fine
+fn prefixes() {
+    let _ = r#match;
+    let _ = r#async;
+    let _ = k "x";
+    let _ = f 'x';
+    let _ = r #x;
+    let _ = c "x";
+    let _ = b 'x';
+    let _ = br "x";
+    let _ = 'r#raw_lifetime;
+    let _ = match x { a => b };
+}
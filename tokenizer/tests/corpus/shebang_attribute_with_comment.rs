@@ -0,0 +1,3 @@
+#! // a comment between the bang and the attribute
+[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,4 @@
+#![allow(dead_code)]
+#![cfg_attr(test, allow(unused))]
+
+fn main() {}
@@ -0,0 +1,11 @@
+fn café() {}
+fn 变量() {}
+struct Ünïcödé;
+
+fn strings() {
+    let _ = "日本語 🎉";
+    let _ = '字';
+    let _ = 'é';
+    let _ = "\u{1F600}";
+    let ŝ = 'ŝ';
+}
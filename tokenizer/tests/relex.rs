@@ -0,0 +1,163 @@
+//! Relexing after an edit has to give the same tokens and errors as tokenizing the edited text
+//! from scratch, in every mode the lexer has.
+
+use std::{fs, path::Path};
+
+use tokenizer::{
+    SrcIterator, Token, TokenizeOptions,
+    lex_error::LexError,
+    relex::{TextEdit, relex},
+    span::FileId,
+    tokenize,
+};
+
+fn modes() -> [TokenizeOptions; 3] {
+    [
+        TokenizeOptions::default(),
+        TokenizeOptions {
+            keep_comments: true,
+            ..Default::default()
+        },
+        TokenizeOptions {
+            lossless: true,
+            ..Default::default()
+        },
+    ]
+}
+
+fn full(src: &str, options: &TokenizeOptions) -> (Vec<Token>, Vec<LexError>) {
+    match tokenize(SrcIterator::new(src), FileId::default(), options) {
+        Ok(tokens) => (tokens.into_vec(), Vec::new()),
+        Err(error) => (error.tokens.into_vec(), error.errors.into_vec()),
+    }
+}
+
+/// Relexes `src` for `edit` and checks it against tokenizing the result, giving the new text
+fn check(src: &str, edit: &TextEdit, options: &TokenizeOptions) -> String {
+    let (tokens, errors) = full(src, options);
+    let new = edit.apply(src);
+    let relexed = relex(tokens, errors, &new, edit, FileId::default(), options);
+    let (tokens, errors) = full(&new, options);
+    assert_eq!(
+        format!("{:?}", relexed.tokens),
+        format!("{:?}", tokens),
+        "tokens after {edit:?} on {src:?} with {options:?}"
+    );
+    assert_eq!(
+        format!("{:?}", relexed.errors),
+        format!("{:?}", errors),
+        "errors after {edit:?} on {src:?} with {options:?}"
+    );
+    assert!(relexed.changed.end <= relexed.tokens.len());
+    new
+}
+
+/// Checks inserting `replacement` in place of the first `find` in `src`
+fn check_replace(src: &str, find: &str, replacement: &str) {
+    let start = src.find(find).unwrap();
+    let edit = TextEdit::new(start..start + find.len(), replacement);
+    for options in modes() {
+        check(src, &edit, &options);
+    }
+}
+
+#[test]
+fn block_comments() {
+    let src = "a /* b */ c d /* e */ f";
+    check_replace(src, "c", "/*");
+    check_replace(src, "c", "/* x */");
+    check_replace(src, "*/", "");
+    check_replace(src, "b", "*/");
+    check_replace("a /* b /* c */ d */ e", "/* c", "");
+    check_replace("a /* b */ c", "/", "");
+}
+
+#[test]
+fn raw_strings() {
+    let src = "a r#\"b\"# c \"d\" r\"e\" f";
+    check_replace(src, "c", "r#\"");
+    check_replace(src, "#\"b", "\"b");
+    check_replace(src, "b\"#", "\"#b\"#");
+    check_replace(src, "r\"e", "r#\"e");
+    // the `#` after the raw byte string isn't part of it
+    let src = "br\"0.1b'\"#u8 ./*#!b'\nb1{#";
+    for options in modes() {
+        check(src, &TextEdit::new(24..25, "\"["), &options);
+    }
+}
+
+#[test]
+fn shebang_and_frontmatter() {
+    let src = "#!/usr/bin/env run\n---cargo\n[dependencies]\n---\nfn main() {}";
+    check_replace(src, "#!", "#![");
+    check_replace(src, "/usr", " [");
+    check_replace(src, "#", "");
+    check_replace(src, "\n---cargo", "");
+    check_replace(src, "---\nfn", "--\nfn");
+    check_replace(src, "fn", "---\n---\nfn");
+    check_replace(src, "main", "x");
+    check_replace("#!//comment\nfn main() {}", "main", "/* */");
+    check_replace("#! /* a */ [b]", "a", "*/");
+    check_replace("\u{FEFF}---\nx\n---\ny", "y", "z");
+    check_replace("\u{FEFF}---\nx\n---\ny", "x", "---");
+}
+
+#[test]
+fn crlf() {
+    let src = "a\r\nb /* c\r\n*/ d\r\n\"e\r\nf\" g";
+    check_replace(src, "b", "x\r\ny");
+    check_replace(src, "\r\n*/", "\n*/");
+    check_replace(src, "d\r", "d");
+    check_replace(src, "g", "\r\n/// h\r\n");
+    check_replace(src, "f", "r\"\r\"");
+}
+
+#[test]
+fn carried_errors() {
+    let src = "1u7 a \"b\"x 2.0f7 § c";
+    check_replace(src, "a", "bb\nbb");
+    check_replace(src, "c", "1u8");
+    check_replace(src, "\"b\"x", "\"b\"");
+    check_replace(src, "§", "");
+}
+
+/// Random edits that tend to open and close things, applied one after the other to every file in
+/// the corpus, so that each edit is relexed from the tokens of the previous one
+#[test]
+fn corpus_edits() {
+    const SNIPPETS: &[&str] = &[
+        "/*", "*/", "r#\"", "\"#", "\"", "x", ".", "\n", "", "1.", "---", "//", "'", "\r\n", "#!",
+        " ", "e3", "b'", "é", "0x", "u7",
+    ];
+    let mut seed: u64 = 1;
+    let mut random = |below: usize| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) as usize % below.max(1)
+    };
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        for options in modes() {
+            let mut src = fs::read_to_string(&path).unwrap();
+            for _ in 0..40 {
+                let mut start = random(src.len() + 1);
+                while !src.is_char_boundary(start) {
+                    start -= 1;
+                }
+                let mut end = (start + random(8)).min(src.len());
+                while !src.is_char_boundary(end) {
+                    end += 1;
+                }
+                let edit = TextEdit::new(start..end, SNIPPETS[random(SNIPPETS.len())]);
+                src = check(&src, &edit, &options);
+            }
+        }
+    }
+}
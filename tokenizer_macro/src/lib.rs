@@ -3,7 +3,9 @@ use core::panic;
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
-#[proc_macro_derive(ParseEnumToken)]
+/// Tries each variant in declaration order and returns the first one that parses.
+/// Variants marked with `#[skip]` are never parsed, they are only constructed by hand.
+#[proc_macro_derive(ParseEnumToken, attributes(skip))]
 pub fn derive_enum_new(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as syn::DeriveInput);
 
@@ -20,6 +22,7 @@ pub fn derive_enum_new(input: TokenStream) -> TokenStream {
 
     let variant_arms = variants
         .iter()
+        .filter(|variant| !variant.attrs.iter().any(|attr| attr.path().is_ident("skip")))
         .map(|variant| {
             let ident = &variant.ident;
             let syn::Fields::Unnamed(field) = &variant.fields else {
@@ -33,7 +36,7 @@ pub fn derive_enum_new(input: TokenStream) -> TokenStream {
             let field_type = &field.ty;
 
             quote::quote! {
-                if let Some((data, new_iter)) = <#field_type as tokenizer_trait::Token>::parse_token(data.clone()) {
+                if let Some((data, new_iter)) = <#field_type as tokenizer_trait::Token>::parse_token(data) {
                     return Some((#name::#ident(data), new_iter));
                 }
             }
@@ -2,7 +2,118 @@ use core::marker::Sized;
 use core::option::Option;
 use std::fmt::Debug;
 
-pub type SrcIterator<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+/// Rust edition the source is written in. Some words are only keywords in later editions, and
+/// some token forms are only reserved in later editions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Edition {
+    Edition2015,
+    Edition2018,
+    Edition2021,
+    #[default]
+    Edition2024,
+}
+
+impl Edition {
+    pub const ALL: &[Edition] = &[
+        Self::Edition2015,
+        Self::Edition2018,
+        Self::Edition2021,
+        Self::Edition2024,
+    ];
+}
+
+/// Cursor into the source text. It is `Copy`, so backtracking is just keeping an old copy
+/// around, and tokens can slice their text straight out of [`SrcIterator::src`].
+#[derive(Debug, Clone, Copy)]
+pub struct SrcIterator<'a> {
+    src: &'a str,
+    offset: usize,
+    edition: Edition,
+}
+
+impl<'a> SrcIterator<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            offset: 0,
+            edition: Edition::default(),
+        }
+    }
+
+    /// Same cursor, moved to byte `offset`, which has to be on a char boundary
+    pub fn with_offset(self, offset: usize) -> Self {
+        assert!(self.src.is_char_boundary(offset));
+        Self { offset, ..self }
+    }
+
+    pub fn with_edition(self, edition: Edition) -> Self {
+        Self { edition, ..self }
+    }
+
+    /// Edition the source is lexed as, tokens whose rules differ between editions check it
+    pub fn edition(&self) -> Edition {
+        self.edition
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Byte offset of the next char that will be returned
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn src(&self) -> &'a str {
+        self.src
+    }
+
+    /// Everything that hasn't been consumed yet
+    pub fn rest(&self) -> &'a str {
+        &self.src[self.offset..]
+    }
+
+    /// Source text from byte offset `start` up to the cursor
+    pub fn slice_from(&self, start: usize) -> &'a str {
+        &self.src[start..self.offset]
+    }
+
+    pub fn starts_with(&self, pattern: &str) -> bool {
+        self.rest().starts_with(pattern)
+    }
+
+    /// Consumes `pattern` if the rest of the input starts with it
+    pub fn eat(&mut self, pattern: &str) -> bool {
+        let matches = self.starts_with(pattern);
+        if matches {
+            self.offset += pattern.len();
+        }
+        matches
+    }
+
+    /// Skips `len` bytes, which have to end on a char boundary
+    pub fn bump(&mut self, len: usize) {
+        self.offset += len;
+        debug_assert!(self.src.is_char_boundary(self.offset));
+    }
+
+    /// Consumes chars as long as `predicate` holds for them
+    pub fn eat_while(&mut self, mut predicate: impl FnMut(char) -> bool) {
+        let rest = self.rest();
+        let len = rest.find(|chr| !predicate(chr)).unwrap_or(rest.len());
+        self.offset += len;
+    }
+}
+
+impl Iterator for SrcIterator<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let chr = self.peek()?;
+        self.offset += chr.len_utf8();
+        Some(chr)
+    }
+}
 
 pub trait Token: Sized + Debug {
     fn parse_token(data: SrcIterator) -> Option<(Self, SrcIterator)>;